@@ -0,0 +1,27 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+/// PSP22 token paying for mints in integration tests
+#[openbrush::implementation(PSP22)]
+#[openbrush::contract]
+pub mod psp22_token {
+    use openbrush::traits::Storage;
+
+    #[ink(storage)]
+    #[derive(Default, Storage)]
+    pub struct Psp22Token {
+        #[storage_field]
+        psp22: psp22::Data,
+    }
+
+    impl Psp22Token {
+        /// Mint `total_supply` tokens to the caller
+        #[ink(constructor)]
+        pub fn new(total_supply: Balance) -> Self {
+            let mut instance = Self::default();
+            let caller = instance.env().caller();
+            psp22::Internal::_mint_to(&mut instance, caller, total_supply)
+                .expect("Should mint total supply");
+            instance
+        }
+    }
+}
@@ -1,6 +1,6 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
-#[openbrush::implementation(PSP34, PSP34Metadata, PSP34Enumerable, Ownable)]
+#[openbrush::implementation(PSP34, PSP34Metadata, PSP34Enumerable, Ownable, AccessControl)]
 #[openbrush::contract]
 pub mod shiden34 {
     use ink::codegen::{
@@ -9,7 +9,9 @@ pub mod shiden34 {
     };
     use openbrush::{
         contracts::{
+            access_control,
             ownable,
+            ownable::OwnableError,
             psp34::{
                 extensions::{
                     enumerable,
@@ -21,9 +23,26 @@ pub mod shiden34 {
         },
         traits::Storage,
     };
-    use payable_mint_pkg::impls::payable_mint::{
-        payable_mint::*,
-        *,
+    #[cfg(feature = "onchain_metadata")]
+    use payable_mint_pkg::impls::onchain_metadata::onchain_metadata::OnchainMetadataImpl;
+    use payable_mint_pkg::impls::{
+        multisig,
+        multisig::multisig::MultisigImpl,
+        payable_mint::{
+            payable_mint::*,
+            types::{
+                IdAssignment,
+                ADMIN,
+                METADATA_MANAGER,
+                MINTER,
+                TREASURER,
+            },
+            *,
+        },
+        payment_splitter,
+        payment_splitter::payment_splitter::PaymentSplitterImpl,
+        royalty,
+        royalty::royalty::RoyaltyImpl,
     };
 
     // Shiden34Contract contract storage
@@ -37,11 +56,19 @@ pub mod shiden34 {
         #[storage_field]
         ownable: ownable::Data,
         #[storage_field]
+        access: access_control::Data,
+        #[storage_field]
         metadata: metadata::Data,
         #[storage_field]
         payable_mint: types::Data,
         #[storage_field]
         enumerable: enumerable::Data,
+        #[storage_field]
+        payment_splitter: payment_splitter::types::Data,
+        #[storage_field]
+        royalty: royalty::types::Data,
+        #[storage_field]
+        multisig: multisig::types::Data,
     }
 
     /// Event emitted when a token transfer occurs.
@@ -67,6 +94,146 @@ pub mod shiden34 {
         approved: bool,
     }
 
+    /// Event emitted when minting is paused.
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    /// Event emitted when minting is unpaused.
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    /// Event emitted when value transferred above the mint price is refunded.
+    #[ink(event)]
+    pub struct Refunded {
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when funds are withdrawn.
+    #[ink(event)]
+    pub struct Withdrawn {
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when new contract owner is proposed.
+    #[ink(event)]
+    pub struct OwnershipProposed {
+        #[ink(topic)]
+        pending_owner: AccountId,
+    }
+
+    /// Event emitted when proposed owner accepts ownership.
+    #[ink(event)]
+    pub struct OwnershipAccepted {
+        #[ink(topic)]
+        previous_owner: Option<AccountId>,
+        #[ink(topic)]
+        new_owner: AccountId,
+    }
+
+    /// Event emitted when pending ownership transfer is cancelled.
+    #[ink(event)]
+    pub struct OwnershipTransferCancelled {
+        #[ink(topic)]
+        pending_owner: AccountId,
+    }
+
+    /// Event emitted when a signer proposes admin action.
+    #[ink(event)]
+    pub struct ActionProposed {
+        #[ink(topic)]
+        proposal_id: u32,
+        #[ink(topic)]
+        proposer: AccountId,
+        expires_at: Timestamp,
+    }
+
+    /// Event emitted when a signer approves admin action.
+    #[ink(event)]
+    pub struct ActionApproved {
+        #[ink(topic)]
+        proposal_id: u32,
+        #[ink(topic)]
+        signer: AccountId,
+    }
+
+    /// Event emitted when approved admin action is executed.
+    #[ink(event)]
+    pub struct ActionExecuted {
+        #[ink(topic)]
+        proposal_id: u32,
+    }
+
+    /// Event emitted when an operation is queued behind the timelock.
+    #[ink(event)]
+    pub struct OperationQueued {
+        #[ink(topic)]
+        operation_id: u32,
+        eta: Timestamp,
+    }
+
+    /// Event emitted when a queued operation is executed.
+    #[ink(event)]
+    pub struct OperationExecuted {
+        #[ink(topic)]
+        operation_id: u32,
+    }
+
+    /// Event emitted when a queued operation is cancelled.
+    #[ink(event)]
+    pub struct OperationCancelled {
+        #[ink(topic)]
+        operation_id: u32,
+    }
+
+    /// Event emitted when price per token is changed.
+    #[ink(event)]
+    pub struct PriceChanged {
+        old: Balance,
+        new: Balance,
+    }
+
+    /// Event emitted when max supply is lowered.
+    #[ink(event)]
+    pub struct MaxSupplyChanged {
+        old: u64,
+        new: u64,
+    }
+
+    /// Event emitted when the collection is permanently closed.
+    #[ink(event)]
+    pub struct CollectionClosed {
+        max_supply: u64,
+    }
+
+    /// Event emitted when the collection is revealed.
+    #[ink(event)]
+    pub struct Revealed {
+        base_uri: String,
+    }
+
+    /// Event emitted when metadata is permanently frozen.
+    #[ink(event)]
+    pub struct PermanentUri {
+        base_uri: String,
+    }
+
+    /// Event emitted when URI of a token changes.
+    #[ink(event)]
+    pub struct MetadataUpdate {
+        #[ink(topic)]
+        token_id: u64,
+    }
+
     // Override event emission methods
     #[overrider(psp34::Internal)]
     fn _emit_transfer_event(&self, from: Option<AccountId>, to: Option<AccountId>, id: Id) {
@@ -83,9 +250,135 @@ pub mod shiden34 {
         });
     }
 
-    impl payable_mint_pkg::impls::payable_mint::payable_mint::Internal for Shiden34Contract {}
+    // Ownership is handed over in two steps, see `propose_owner` and `accept_ownership`
+    #[overrider(Ownable)]
+    #[openbrush::modifiers(ownable::only_owner)]
+    fn transfer_ownership(&mut self, new_owner: Option<AccountId>) -> Result<(), OwnableError> {
+        let new_owner = new_owner.ok_or(OwnableError::NewOwnerIsNotSet)?;
+        PayableMintImpl::propose_owner(self, new_owner).map_err(|_| OwnableError::CallerIsNotOwner)
+    }
+
+    // Renouncing would leave the roles with the former owner, so it is not supported
+    #[overrider(Ownable)]
+    #[openbrush::modifiers(ownable::only_owner)]
+    fn renounce_ownership(&mut self) -> Result<(), OwnableError> {
+        Err(OwnableError::NewOwnerIsNotSet)
+    }
+
+    impl payable_mint_pkg::impls::payable_mint::payable_mint::Internal for Shiden34Contract {
+        fn _emit_refund_event(&self, to: AccountId, amount: Balance) {
+            self.env().emit_event(Refunded { to, amount });
+        }
+
+        fn _emit_price_changed_event(&self, old: Balance, new: Balance) {
+            self.env().emit_event(PriceChanged { old, new });
+        }
+
+        fn _emit_withdrawn_event(&self, to: AccountId, amount: Balance) {
+            self.env().emit_event(Withdrawn { to, amount });
+        }
+
+        fn _emit_ownership_proposed_event(&self, pending_owner: AccountId) {
+            self.env().emit_event(OwnershipProposed { pending_owner });
+        }
+
+        fn _emit_ownership_accepted_event(
+            &self,
+            previous_owner: Option<AccountId>,
+            new_owner: AccountId,
+        ) {
+            self.env().emit_event(OwnershipAccepted {
+                previous_owner,
+                new_owner,
+            });
+        }
+
+        fn _emit_ownership_transfer_cancelled_event(&self, pending_owner: AccountId) {
+            self.env()
+                .emit_event(OwnershipTransferCancelled { pending_owner });
+        }
+
+        fn _emit_operation_queued_event(&self, operation_id: u32, eta: Timestamp) {
+            self.env().emit_event(OperationQueued { operation_id, eta });
+        }
+
+        fn _emit_operation_executed_event(&self, operation_id: u32) {
+            self.env().emit_event(OperationExecuted { operation_id });
+        }
+
+        fn _emit_operation_cancelled_event(&self, operation_id: u32) {
+            self.env().emit_event(OperationCancelled { operation_id });
+        }
+
+        fn _emit_max_supply_changed_event(&self, old: u64, new: u64) {
+            self.env().emit_event(MaxSupplyChanged { old, new });
+        }
+
+        fn _emit_collection_closed_event(&self, max_supply: u64) {
+            self.env().emit_event(CollectionClosed { max_supply });
+        }
+
+        fn _emit_revealed_event(&self, base_uri: String) {
+            self.env().emit_event(Revealed { base_uri });
+        }
+
+        fn _emit_metadata_update_event(&self, token_id: u64) {
+            self.env().emit_event(MetadataUpdate { token_id });
+        }
+
+        fn _emit_permanent_uri_event(&self, base_uri: String) {
+            self.env().emit_event(PermanentUri { base_uri });
+        }
+
+        fn _emit_paused_event(&self, account: AccountId) {
+            self.env().emit_event(Paused { account });
+        }
+
+        fn _emit_unpaused_event(&self, account: AccountId) {
+            self.env().emit_event(Unpaused { account });
+        }
+    }
     impl PayableMintImpl for Shiden34Contract {}
 
+    impl payment_splitter::payment_splitter::Internal for Shiden34Contract {
+        fn _reserved_balance(&self) -> Balance {
+            payable_mint_pkg::impls::payable_mint::payable_mint::Internal::reserved_balance(self)
+        }
+    }
+    impl PaymentSplitterImpl for Shiden34Contract {}
+
+    impl RoyaltyImpl for Shiden34Contract {}
+
+    #[cfg(feature = "onchain_metadata")]
+    impl OnchainMetadataImpl for Shiden34Contract {}
+
+    impl multisig::multisig::Internal for Shiden34Contract {
+        fn _emit_action_proposed_event(
+            &self,
+            proposal_id: u32,
+            proposer: AccountId,
+            expires_at: Timestamp,
+        ) {
+            self.env().emit_event(ActionProposed {
+                proposal_id,
+                proposer,
+                expires_at,
+            });
+        }
+
+        fn _emit_action_approved_event(&self, proposal_id: u32, signer: AccountId) {
+            self.env().emit_event(ActionApproved {
+                proposal_id,
+                signer,
+            });
+        }
+
+        fn _emit_action_executed_event(&self, proposal_id: u32) {
+            self.env().emit_event(ActionExecuted { proposal_id });
+        }
+    }
+    impl MultisigImpl for Shiden34Contract {}
+
     impl Shiden34Contract {
         #[ink(constructor)]
         pub fn new(
@@ -98,6 +391,10 @@ pub mod shiden34 {
             let mut instance = Self::default();
             let caller = instance.env().caller();
             ownable::InternalImpl::_init_with_owner(&mut instance, caller);
+            access_control::InternalImpl::_init_with_admin(&mut instance, Some(caller));
+            for role in [ADMIN, MINTER, METADATA_MANAGER, TREASURER] {
+                access_control::InternalImpl::_setup_role(&mut instance, role, Some(caller));
+            }
             let collection_id = PSP34Impl::collection_id(&instance);
             metadata::InternalImpl::_set_attribute(
                 &mut instance,
@@ -121,6 +418,25 @@ pub mod shiden34 {
             instance.payable_mint.price_per_mint = price_per_mint;
             instance.payable_mint.last_token_id = 0;
             instance.payable_mint.max_amount = 1;
+            instance.royalty.default_receiver = caller;
+            instance
+        }
+
+        /// Same as `new`, but token metadata is shuffled by the seed whose Keccak-256 hash
+        /// is `seed_commitment`. The owner reveals it once the sale is over, so minters
+        /// can't tell which metadata their tokens get
+        #[ink(constructor)]
+        pub fn new_with_random_ids(
+            name: String,
+            symbol: String,
+            base_uri: String,
+            max_supply: u64,
+            price_per_mint: Balance,
+            seed_commitment: [u8; 32],
+        ) -> Self {
+            let mut instance = Self::new(name, symbol, base_uri, max_supply, price_per_mint);
+            instance.payable_mint.id_assignment = IdAssignment::Random;
+            instance.payable_mint.random_seed_commitment = Some(seed_commitment);
             instance
         }
     }
@@ -137,12 +453,32 @@ pub mod shiden34 {
             },
             prelude::string::String,
         };
-        use openbrush::contracts::psp34::PSP34Impl;
-        use payable_mint_pkg::impls::payable_mint::{
-            payable_mint::Internal,
-            types::Shiden34Error,
+        use openbrush::contracts::{
+            access_control::{
+                AccessControlImpl,
+                DEFAULT_ADMIN_ROLE,
+            },
+            ownable::OwnableImpl,
+            psp34::PSP34Impl,
+        };
+        use payable_mint_pkg::impls::{
+            multisig::types::AdminAction,
+            payable_mint::{
+                payable_mint::Internal,
+                types::{
+                    DutchAuction,
+                    SalePhase,
+                    Shiden34Error,
+                    TimelockOperation,
+                    TimelockedOperation,
+                    MAX_AIRDROP_AMOUNT,
+                    MAX_TIMELOCK_DELAY,
+                },
+            },
+            payment_splitter::types::MAX_TOTAL_SHARES,
         };
         const PRICE: Balance = 100_000_000_000_000_000;
+        const VOUCHER_SIGNER_KEY: [u8; 32] = [0x11; 32];
         const BASE_URI: &str = "ipfs://myIpfsUri/";
         const MAX_SUPPLY: u64 = 10;
 
@@ -188,6 +524,15 @@ pub mod shiden34 {
             )
         }
 
+        // use a contract account apart from alice, who is the default callee
+        fn init_with_contract_callee() -> Shiden34Contract {
+            let contract = AccountId::from([0x42; 32]);
+            test::set_callee::<ink::env::DefaultEnvironment>(contract);
+            let sh34 = init();
+            set_balance(contract, sh34.env().minimum_balance());
+            sh34
+        }
+
         #[ink::test]
         fn mint_single_works() {
             let mut sh34 = init();
@@ -303,128 +648,1823 @@ pub mod shiden34 {
         }
 
         #[ink::test]
-        fn withdrawal_works() {
+        fn roles_works() {
             let mut sh34 = init();
             let accounts = default_accounts();
-            set_balance(accounts.bob, PRICE);
+            let missing_role = Err(PSP34Error::Custom(String::from("AC::MissingRole")));
+            for role in [ADMIN, MINTER, METADATA_MANAGER, TREASURER] {
+                assert!(AccessControlImpl::has_role(
+                    &sh34,
+                    role,
+                    Some(accounts.alice)
+                ));
+                assert!(!AccessControlImpl::has_role(
+                    &sh34,
+                    role,
+                    Some(accounts.bob)
+                ));
+            }
+
             set_sender(accounts.bob);
+            assert_eq!(sh34.airdrop(vec![(accounts.bob, 1)]), missing_role);
+            assert_eq!(sh34.set_base_uri(BASE_URI.into()), missing_role);
+            assert_eq!(sh34.withdraw(), missing_role);
+            assert_eq!(sh34.set_max_mint_amount(2), missing_role);
 
-            assert!(pay_with_call!(sh34.mint_next(), PRICE).is_ok());
-            let expected_contract_balance = PRICE + sh34.env().minimum_balance();
-            assert_eq!(sh34.env().balance(), expected_contract_balance);
+            // each role unlocks only its own messages
+            set_sender(accounts.alice);
+            assert!(AccessControlImpl::grant_role(&mut sh34, MINTER, Some(accounts.bob)).is_ok());
+            set_sender(accounts.bob);
+            assert!(sh34.airdrop(vec![(accounts.bob, 1)]).is_ok());
+            assert_eq!(sh34.set_base_uri(BASE_URI.into()), missing_role);
 
-            // Bob fails to withdraw
+            set_sender(accounts.alice);
+            assert!(
+                AccessControlImpl::grant_role(&mut sh34, METADATA_MANAGER, Some(accounts.bob))
+                    .is_ok()
+            );
             set_sender(accounts.bob);
-            assert!(sh34.withdraw().is_err());
-            assert_eq!(sh34.env().balance(), expected_contract_balance);
+            assert!(sh34.set_base_uri(BASE_URI.into()).is_ok());
+            assert_eq!(sh34.withdraw(), missing_role);
 
-            // Alice (contract owner) withdraws. Existential minimum is still set
             set_sender(accounts.alice);
+            assert!(
+                AccessControlImpl::grant_role(&mut sh34, TREASURER, Some(accounts.bob)).is_ok()
+            );
+            set_sender(accounts.bob);
             assert!(sh34.withdraw().is_ok());
-            // assert_eq!(sh34.env().balance(), sh34.env().minimum_balance());
+            assert_eq!(sh34.set_max_mint_amount(2), missing_role);
+
+            set_sender(accounts.alice);
+            assert!(AccessControlImpl::grant_role(&mut sh34, ADMIN, Some(accounts.bob)).is_ok());
+            assert!(AccessControlImpl::revoke_role(&mut sh34, MINTER, Some(accounts.bob)).is_ok());
+            set_sender(accounts.bob);
+            assert!(sh34.set_max_mint_amount(2).is_ok());
+            assert_eq!(sh34.airdrop(vec![(accounts.bob, 1)]), missing_role);
         }
 
         #[ink::test]
-        fn token_uri_works() {
+        fn two_step_ownership_transfer_works() {
             let mut sh34 = init();
             let accounts = default_accounts();
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.propose_owner(accounts.bob),
+                Err(PSP34Error::Custom(String::from("O::CallerIsNotOwner")))
+            );
+
             set_sender(accounts.alice);
+            assert_eq!(
+                sh34.cancel_ownership_transfer(),
+                Err(PSP34Error::Custom(Shiden34Error::NoPendingOwner.as_str()))
+            );
+            assert!(sh34.propose_owner(accounts.charlie).is_ok());
+            assert!(sh34.cancel_ownership_transfer().is_ok());
+            assert_eq!(sh34.pending_owner(), None);
 
-            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
-            assert!(sh34.mint_next().is_ok());
-            // return error if request is for not yet minted token
-            assert_eq!(sh34.token_uri(42), Err(TokenNotExists));
+            // one-shot transfer only proposes the new owner, renouncing is disabled
             assert_eq!(
-                sh34.token_uri(1),
-                Ok(String::from(BASE_URI.to_owned() + "1.json"))
+                Ownable::transfer_ownership(&mut sh34, None),
+                Err(OwnableError::NewOwnerIsNotSet)
             );
+            assert_eq!(
+                Ownable::renounce_ownership(&mut sh34),
+                Err(OwnableError::NewOwnerIsNotSet)
+            );
+            assert!(Ownable::transfer_ownership(&mut sh34, Some(accounts.bob)).is_ok());
+            assert_eq!(OwnableImpl::owner(&sh34), Some(accounts.alice));
+            assert_eq!(sh34.pending_owner(), Some(accounts.bob));
 
-            // return error if request is for not yet minted token
-            assert_eq!(sh34.token_uri(42), Err(TokenNotExists));
+            // ownership stays with alice until bob accepts it
+            set_sender(accounts.charlie);
+            assert_eq!(
+                sh34.accept_ownership(),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::CallerIsNotPendingOwner.as_str()
+                ))
+            );
+            assert_eq!(OwnableImpl::owner(&sh34), Some(accounts.alice));
+
+            set_sender(accounts.bob);
+            assert!(sh34.accept_ownership().is_ok());
+            assert_eq!(OwnableImpl::owner(&sh34), Some(accounts.bob));
+            assert_eq!(sh34.pending_owner(), None);
+
+            // admin and operational roles move over with ownership
+            for role in [
+                DEFAULT_ADMIN_ROLE,
+                ADMIN,
+                MINTER,
+                METADATA_MANAGER,
+                TREASURER,
+            ] {
+                assert!(AccessControlImpl::has_role(&sh34, role, Some(accounts.bob)));
+                assert!(!AccessControlImpl::has_role(
+                    &sh34,
+                    role,
+                    Some(accounts.alice)
+                ));
+            }
+            assert!(sh34.withdraw().is_ok());
 
-            // verify token_uri when baseUri is empty
             set_sender(accounts.alice);
-            assert!(sh34.set_base_uri(String::from("")).is_ok());
             assert_eq!(
-                sh34.token_uri(1),
-                Ok("".to_owned() + &String::from("1.json"))
+                sh34.propose_owner(accounts.alice),
+                Err(PSP34Error::Custom(String::from("O::CallerIsNotOwner")))
+            );
+            assert_eq!(
+                Ownable::transfer_ownership(&mut sh34, Some(accounts.alice)),
+                Err(OwnableError::CallerIsNotOwner)
+            );
+            assert_eq!(
+                sh34.withdraw(),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
             );
-        }
 
-        #[ink::test]
-        fn owner_is_set() {
-            let accounts = default_accounts();
-            let sh34 = init();
-            assert_eq!(Ownable::owner(&sh34).unwrap(), accounts.alice);
+            // OwnershipProposed, OwnershipTransferCancelled, OwnershipProposed,
+            // OwnershipAccepted and Withdrawn events
+            assert_eq!(5, ink::env::test::recorded_events().count());
         }
 
         #[ink::test]
-        fn set_base_uri_works() {
+        fn multisig_works() {
+            let mut sh34 = init_with_contract_callee();
             let accounts = default_accounts();
-            const NEW_BASE_URI: &str = "new_uri/";
-            let mut sh34 = init();
+            set_balance(accounts.bob, PRICE);
+            set_sender(accounts.bob);
+            assert!(pay_with_call!(sh34.mint_next(), PRICE).is_ok());
 
             set_sender(accounts.alice);
-            let collection_id = PSP34Impl::collection_id(&sh34);
-            assert!(sh34.set_base_uri(NEW_BASE_URI.into()).is_ok());
+            let signers = vec![accounts.bob, accounts.charlie, accounts.django];
             assert_eq!(
-                PSP34MetadataImpl::get_attribute(&sh34, collection_id, String::from("baseUri")),
-                Some(String::from(NEW_BASE_URI))
+                sh34.configure_multisig(signers.clone(), 4, 100),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::InvalidMultisigConfig.as_str()
+                ))
             );
-            set_sender(accounts.bob);
+            assert!(sh34.configure_multisig(signers.clone(), 2, 100).is_ok());
             assert_eq!(
-                sh34.set_base_uri(NEW_BASE_URI.into()),
-                Err(PSP34Error::Custom(String::from("O::CallerIsNotOwner")))
+                sh34.configure_multisig(signers, 1, 100),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::MultisigAlreadyConfigured.as_str()
+                ))
             );
-        }
 
-        #[ink::test]
-        fn check_supply_overflow_ok() {
-            let max_supply = u64::MAX - 1;
-            let mut sh34 = Shiden34Contract::new(
-                String::from("Shiden34"),
-                String::from("SH34"),
-                String::from(BASE_URI),
-                max_supply,
-                PRICE,
+            // admin actions can't be called directly anymore
+            assert_eq!(
+                sh34.withdraw(),
+                Err(PSP34Error::Custom(Shiden34Error::MultisigRequired.as_str()))
             );
-            sh34.payable_mint.last_token_id = max_supply - 1;
-
-            // check case when last_token_id.add(mint_amount) if more than u64::MAX
-            assert!(sh34.set_max_mint_amount(u64::MAX).is_ok());
             assert_eq!(
-                sh34.check_amount(3),
-                Err(PSP34Error::Custom(Shiden34Error::CollectionIsFull.as_str()))
+                sh34.set_price(PRICE * 2),
+                Err(PSP34Error::Custom(Shiden34Error::MultisigRequired.as_str()))
             );
-
-            // check case when mint_amount is 0
+            for result in [
+                sh34.lock_price(),
+                sh34.close_collection(),
+                sh34.reveal(String::from(BASE_URI)),
+                sh34.set_token_uri(1, Some(String::from("ipfs://token/"))),
+                sh34.add_payee(accounts.alice, 1),
+                sh34.set_referral_bps(10_000),
+                sh34.add_sale_phase(sale_phase(0, 10, 10, 0, 100)),
+                sh34.update_sale_phase(0, sale_phase(0, 10, 10, 0, 100)),
+                sh34.set_dutch_auction(None),
+                sh34.set_psp22_payment(Some(accounts.eve), 0),
+                sh34.set_voucher_signer(None),
+            ] {
+                assert_eq!(
+                    result,
+                    Err(PSP34Error::Custom(Shiden34Error::MultisigRequired.as_str()))
+                );
+            }
             assert_eq!(
-                sh34.check_amount(0),
+                sh34.propose_action(AdminAction::SetPrice(PRICE * 2)),
                 Err(PSP34Error::Custom(
-                    Shiden34Error::CannotMintZeroTokens.as_str()
+                    Shiden34Error::NotMultisigSigner.as_str()
                 ))
             );
-        }
 
-        #[ink::test]
-        fn check_value_overflow_ok() {
-            let max_supply = u64::MAX;
-            let price = u128::MAX as u128;
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.propose_action(AdminAction::WithdrawTo(accounts.eve, PRICE)),
+                Ok(0)
+            );
+            assert_eq!(
+                sh34.approve_action(0),
+                Err(PSP34Error::Custom(Shiden34Error::AlreadyApproved.as_str()))
+            );
+            assert_eq!(
+                sh34.execute_action(0),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::ThresholdNotReached.as_str()
+                ))
+            );
+
+            set_sender(accounts.charlie);
+            assert!(sh34.approve_action(0).is_ok());
+            assert_eq!(sh34.approval_count(0), 2);
+            set_balance(accounts.eve, 0);
+            assert!(sh34.execute_action(0).is_ok());
+            assert_eq!(
+                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.eve),
+                Ok(PRICE)
+            );
+            assert_eq!(
+                sh34.execute_action(0),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::ProposalAlreadyExecuted.as_str()
+                ))
+            );
+
+            assert_eq!(sh34.propose_action(AdminAction::LockPrice), Ok(1));
+            set_sender(accounts.bob);
+            assert!(sh34.approve_action(1).is_ok());
+            assert!(sh34.execute_action(1).is_ok());
+            assert!(sh34.is_price_locked());
+            assert_eq!(
+                sh34.propose_action(AdminAction::AddPayee(accounts.eve, 1)),
+                Ok(2)
+            );
+            set_sender(accounts.charlie);
+            assert!(sh34.approve_action(2).is_ok());
+            assert!(sh34.execute_action(2).is_ok());
+            assert_eq!(sh34.payees(), vec![accounts.eve]);
+            assert!(sh34
+                .propose_action(AdminAction::SetReferralBps(500))
+                .is_ok());
+            set_sender(accounts.bob);
+            assert!(sh34.approve_action(3).is_ok());
+            assert!(sh34.execute_action(3).is_ok());
+            assert_eq!(sh34.referral_bps(), 500);
+
+            // stale proposals expire
+            assert_eq!(sh34.propose_action(AdminAction::SetMaxMintAmount(5)), Ok(4));
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(101);
+            set_sender(accounts.django);
+            assert_eq!(
+                sh34.approve_action(4),
+                Err(PSP34Error::Custom(Shiden34Error::ProposalExpired.as_str()))
+            );
+            assert_eq!(sh34.get_max_mint_amount(), 1);
+
+            // Transfer, ActionProposed, ActionApproved, Withdrawn, ActionExecuted,
+            // three times ActionProposed, ActionApproved and ActionExecuted, and ActionProposed events
+            assert_eq!(15, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn timelock_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_timelock_delay(100),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.set_timelock_delay(MAX_TIMELOCK_DELAY + 1),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::InvalidTimelockDelay.as_str()
+                ))
+            );
+            assert!(sh34.set_timelock_delay(100).is_ok());
+            assert_eq!(
+                sh34.set_timelock_delay(50),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::InvalidTimelockDelay.as_str()
+                ))
+            );
+
+            // changes are queued instead of applied
+            assert!(sh34.set_max_mint_amount(5).is_ok());
+            assert!(sh34.set_base_uri(String::from("ipfs://other/")).is_ok());
+            assert_eq!(sh34.get_max_mint_amount(), 1);
+            assert_eq!(
+                sh34.pending_operations(),
+                vec![
+                    (
+                        0,
+                        TimelockedOperation {
+                            operation: TimelockOperation::SetMaxMintAmount(5),
+                            eta: 100,
+                        }
+                    ),
+                    (
+                        1,
+                        TimelockedOperation {
+                            operation: TimelockOperation::SetBaseUri(String::from("ipfs://other/")),
+                            eta: 100,
+                        }
+                    ),
+                ]
+            );
+            assert_eq!(
+                sh34.execute_operation(0),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::TimelockNotExpired.as_str()
+                ))
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.cancel_operation(1),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+            set_sender(accounts.alice);
+            assert!(sh34.cancel_operation(1).is_ok());
+
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(100);
+            assert!(sh34.execute_operation(0).is_ok());
+            assert_eq!(sh34.get_max_mint_amount(), 5);
+            assert_eq!(sh34.pending_operations(), vec![]);
+            assert_eq!(
+                sh34.execute_operation(1),
+                Err(PSP34Error::Custom(Shiden34Error::UnknownOperation.as_str()))
+            );
+        }
+
+        #[ink::test]
+        fn timelocked_withdraw_and_reveal_works() {
+            let mut sh34 = init_with_contract_callee();
+            let accounts = default_accounts();
+            set_balance(accounts.bob, PRICE);
+            set_sender(accounts.bob);
+            assert!(pay_with_call!(sh34.mint_next(), PRICE).is_ok());
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_treasury(Some(accounts.charlie)).is_ok());
+            assert!(sh34.set_timelock_delay(100).is_ok());
+            assert!(sh34.withdraw().is_ok());
+            assert!(sh34.reveal(String::from("ipfs://revealed/")).is_ok());
+            assert!(!sh34.is_revealed());
+            let token = AccountId::from([0x07; 32]);
+            assert!(sh34.withdraw_psp22(token).is_ok());
+            assert!(sh34.add_payee(accounts.django, 1).is_ok());
+            assert_eq!(sh34.payees(), vec![]);
+            assert_eq!(
+                sh34.pending_operations(),
+                vec![
+                    (
+                        0,
+                        TimelockedOperation {
+                            operation: TimelockOperation::Withdraw(accounts.charlie),
+                            eta: 100,
+                        }
+                    ),
+                    (
+                        1,
+                        TimelockedOperation {
+                            operation: TimelockOperation::Reveal(String::from("ipfs://revealed/")),
+                            eta: 100,
+                        }
+                    ),
+                    (
+                        2,
+                        TimelockedOperation {
+                            operation: TimelockOperation::WithdrawPsp22(token, accounts.charlie),
+                            eta: 100,
+                        }
+                    ),
+                    (
+                        3,
+                        TimelockedOperation {
+                            operation: TimelockOperation::AddPayee(accounts.django, 1),
+                            eta: 100,
+                        }
+                    ),
+                ]
+            );
+
+            // changing treasury doesn't redirect the announced withdrawal
+            assert!(sh34.set_treasury(Some(accounts.eve)).is_ok());
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(100);
+            set_balance(accounts.charlie, 0);
+            set_balance(accounts.eve, 0);
+            assert!(sh34.execute_operation(0).is_ok());
+            assert_eq!(
+                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.charlie),
+                Ok(PRICE)
+            );
+            assert_eq!(
+                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.eve),
+                Ok(0)
+            );
+
+            assert!(sh34.execute_operation(1).is_ok());
+            assert!(sh34.is_revealed());
+            assert_eq!(
+                sh34.token_uri(1),
+                Ok(String::from("ipfs://revealed/1.json"))
+            );
+
+            assert!(sh34.execute_operation(3).is_ok());
+            assert_eq!(sh34.payees(), vec![accounts.django]);
+            assert_eq!(sh34.shares(accounts.django), 1);
+        }
+
+        #[ink::test]
+        fn multisig_with_timelock_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            assert!(sh34.set_timelock_delay(100).is_ok());
+            assert!(sh34.set_max_mint_amount(5).is_ok());
+            assert_eq!(sh34.pending_operations().len(), 1);
+
+            // operations queued before the multisig is configured are cancelled
+            let signers = vec![accounts.bob, accounts.charlie];
+            assert!(sh34.configure_multisig(signers, 2, 1000).is_ok());
+            assert_eq!(sh34.pending_operations(), vec![]);
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(
+                sh34.execute_operation(0),
+                Err(PSP34Error::Custom(Shiden34Error::UnknownOperation.as_str()))
+            );
+            assert_eq!(sh34.get_max_mint_amount(), 1);
+
+            // approved actions are queued while the timelock delay is set
+            set_sender(accounts.bob);
+            assert_eq!(sh34.propose_action(AdminAction::SetMaxMintAmount(3)), Ok(0));
+            set_sender(accounts.charlie);
+            assert!(sh34.approve_action(0).is_ok());
+            assert!(sh34.execute_action(0).is_ok());
+            assert_eq!(sh34.get_max_mint_amount(), 1);
+            assert_eq!(
+                sh34.pending_operations(),
+                vec![(
+                    1,
+                    TimelockedOperation {
+                        operation: TimelockOperation::SetMaxMintAmount(3),
+                        eta: 200,
+                    }
+                )]
+            );
+            assert_eq!(
+                sh34.execute_operation(1),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::TimelockNotExpired.as_str()
+                ))
+            );
+
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(200);
+            assert!(sh34.execute_operation(1).is_ok());
+            assert_eq!(sh34.get_max_mint_amount(), 3);
+        }
+
+        #[ink::test]
+        fn withdrawal_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_balance(accounts.bob, PRICE);
+            set_sender(accounts.bob);
+
+            assert!(pay_with_call!(sh34.mint_next(), PRICE).is_ok());
+            let expected_contract_balance = PRICE + sh34.env().minimum_balance();
+            assert_eq!(sh34.env().balance(), expected_contract_balance);
+
+            // Bob fails to withdraw
+            set_sender(accounts.bob);
+            assert!(sh34.withdraw().is_err());
+            assert_eq!(sh34.env().balance(), expected_contract_balance);
+
+            // Alice (contract owner) withdraws. Existential minimum is still set
+            set_sender(accounts.alice);
+            assert!(sh34.withdraw().is_ok());
+            // assert_eq!(sh34.env().balance(), sh34.env().minimum_balance());
+        }
+
+        #[ink::test]
+        fn withdraw_to_treasury_works() {
+            let mut sh34 = init_with_contract_callee();
+            let accounts = default_accounts();
+            set_balance(accounts.bob, PRICE);
+            set_sender(accounts.bob);
+            assert!(pay_with_call!(sh34.mint_next(), PRICE).is_ok());
+
+            assert_eq!(
+                sh34.set_treasury(Some(accounts.charlie)),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+            assert_eq!(
+                sh34.withdraw_to(accounts.bob, PRICE),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_treasury(Some(accounts.charlie)).is_ok());
+            assert_eq!(sh34.treasury(), Some(accounts.charlie));
+            assert_eq!(
+                sh34.withdraw_to(accounts.django, PRICE + 1),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::InsufficientWithdrawableBalance.as_str()
+                ))
+            );
+
+            set_balance(accounts.django, 0);
+            assert!(sh34.withdraw_to(accounts.django, PRICE / 4).is_ok());
+            assert_eq!(
+                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.django),
+                Ok(PRICE / 4)
+            );
+
+            set_balance(accounts.charlie, 0);
+            assert!(sh34.withdraw().is_ok());
+            assert_eq!(
+                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.charlie),
+                Ok(PRICE - PRICE / 4)
+            );
+            assert_eq!(sh34.env().balance(), sh34.env().minimum_balance());
+
+            // Transfer and two Withdrawn events
+            assert_eq!(3, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn referral_rewards_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.set_referral_bps(10_001),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::InvalidReferralShare.as_str()
+                ))
+            );
+            assert!(sh34.set_referral_bps(1_000).is_ok());
+
+            set_sender(accounts.bob);
+            set_balance(accounts.bob, PRICE * 2);
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            assert_eq!(
+                sh34.mint_with_referrer(accounts.charlie, 1, accounts.bob),
+                Err(PSP34Error::Custom(Shiden34Error::SelfReferral.as_str()))
+            );
+            assert_eq!(
+                sh34.mint_with_referrer(accounts.charlie, 1, accounts.charlie),
+                Err(PSP34Error::Custom(Shiden34Error::SelfReferral.as_str()))
+            );
+            assert!(pay_with_call!(
+                sh34.mint_with_referrer(accounts.bob, 1, accounts.charlie),
+                PRICE
+            )
+            .is_ok());
+            assert_eq!(sh34.referral_rewards(accounts.charlie), PRICE / 10);
+
+            set_sender(accounts.charlie);
+            set_balance(accounts.charlie, 0);
+            assert!(sh34.claim_referral_rewards().is_ok());
+            assert_eq!(
+                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.charlie),
+                Ok(PRICE / 10)
+            );
+            assert_eq!(
+                sh34.claim_referral_rewards(),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::NoReferralRewards.as_str()
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn set_price_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_price(PRICE * 2),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_price(PRICE * 2).is_ok());
+            assert_eq!(sh34.price(), PRICE * 2);
+            assert_eq!(1, ink::env::test::recorded_events().count());
+
+            assert!(sh34.lock_price().is_ok());
+            assert!(sh34.is_price_locked());
+            assert_eq!(
+                sh34.set_price(PRICE),
+                Err(PSP34Error::Custom(Shiden34Error::PriceLocked.as_str()))
+            );
+            assert_eq!(sh34.price(), PRICE * 2);
+        }
+
+        #[ink::test]
+        fn payment_splitter_works() {
+            let mut sh34 = init_with_contract_callee();
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.add_payee(accounts.bob, 0),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidShares.as_str()))
+            );
+            assert_eq!(
+                sh34.add_payee(accounts.bob, MAX_TOTAL_SHARES + 1),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidShares.as_str()))
+            );
+            assert!(sh34.add_payee(accounts.bob, 1).is_ok());
+            assert_eq!(
+                sh34.add_payee(accounts.charlie, MAX_TOTAL_SHARES),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidShares.as_str()))
+            );
+            assert!(sh34.add_payee(accounts.charlie, 3).is_ok());
+            assert_eq!(
+                sh34.add_payee(accounts.charlie, 1),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::PayeeAlreadyExists.as_str()
+                ))
+            );
+            assert_eq!(sh34.payees(), vec![accounts.bob, accounts.charlie]);
+            assert_eq!(sh34.total_shares(), 4);
+            assert_eq!(
+                sh34.withdraw(),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::PaymentSplitterActive.as_str()
+                ))
+            );
+            assert_eq!(
+                sh34.withdraw_psp22(accounts.eve),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::PaymentSplitterActive.as_str()
+                ))
+            );
+
+            set_sender(accounts.django);
+            set_balance(accounts.django, PRICE * 4);
+            for _ in 0..4 {
+                assert!(pay_with_call!(sh34.mint_next(), PRICE).is_ok());
+            }
+            assert_eq!(sh34.releasable(accounts.bob), PRICE);
+            assert_eq!(sh34.releasable(accounts.charlie), PRICE * 3);
+
+            set_balance(accounts.bob, 0);
+            assert!(sh34.release(accounts.bob).is_ok());
+            assert_eq!(
+                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.bob),
+                Ok(PRICE)
+            );
+            assert_eq!(sh34.released(accounts.bob), PRICE);
+            assert_eq!(sh34.total_released(), PRICE);
+            assert_eq!(
+                sh34.release(accounts.bob),
+                Err(PSP34Error::Custom(Shiden34Error::NoPaymentDue.as_str()))
+            );
+            assert_eq!(sh34.releasable(accounts.charlie), PRICE * 3);
+            // PSP22 funds are accounted per token, see the PSP22 integration test
+            assert_eq!(sh34.psp22_released(accounts.eve, accounts.bob), 0);
+            assert_eq!(sh34.psp22_total_released(accounts.eve), 0);
+
+            // payees are locked once funds are released
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.add_payee(accounts.django, 1),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::PaymentSplitterLocked.as_str()
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn royalty_info_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            assert_eq!(sh34.default_royalty(), (accounts.alice, 0));
+            assert_eq!(sh34.royalty_info(Id::U64(1), PRICE), (accounts.alice, 0));
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_default_royalty(accounts.bob, 500),
+                Err(PSP34Error::Custom(String::from("O::CallerIsNotOwner")))
+            );
+
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.set_default_royalty(accounts.bob, 10_001),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidRoyalty.as_str()))
+            );
+            assert!(sh34.set_default_royalty(accounts.bob, 500).is_ok());
+            assert!(sh34
+                .set_token_royalty(Id::U64(2), accounts.charlie, 1_000)
+                .is_ok());
+            assert_eq!(
+                sh34.royalty_info(Id::U64(1), PRICE),
+                (accounts.bob, PRICE / 20)
+            );
+            assert_eq!(
+                sh34.royalty_info(Id::U64(2), PRICE),
+                (accounts.charlie, PRICE / 10)
+            );
+
+            assert!(sh34.reset_token_royalty(Id::U64(2)).is_ok());
+            assert_eq!(
+                sh34.royalty_info(Id::U64(2), PRICE),
+                (accounts.bob, PRICE / 20)
+            );
+        }
+
+        #[ink::test]
+        fn token_uri_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            assert!(sh34.mint_next().is_ok());
+            // return error if request is for not yet minted token
+            assert_eq!(sh34.token_uri(42), Err(TokenNotExists));
+            assert_eq!(
+                sh34.token_uri(1),
+                Ok(String::from(BASE_URI.to_owned() + "1.json"))
+            );
+
+            // return error if request is for not yet minted token
+            assert_eq!(sh34.token_uri(42), Err(TokenNotExists));
+
+            // verify token_uri when baseUri is empty
+            set_sender(accounts.alice);
+            assert!(sh34.set_base_uri(String::from("")).is_ok());
+            assert_eq!(
+                sh34.token_uri(1),
+                Ok("".to_owned() + &String::from("1.json"))
+            );
+        }
+
+        #[ink::test]
+        fn token_uri_override_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            assert!(sh34.mint_next().is_ok());
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_token_uri(1, Some(String::from("ipfs://fixed.json"))),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+
+            set_sender(accounts.alice);
+            assert!(sh34
+                .set_token_uri(1, Some(String::from("ipfs://fixed.json")))
+                .is_ok());
+            assert_eq!(sh34.token_uri(1), Ok(String::from("ipfs://fixed.json")));
+
+            // removing the override falls back to baseUri
+            assert!(sh34.set_token_uri(1, None).is_ok());
+            assert_eq!(
+                sh34.token_uri(1),
+                Ok(String::from(BASE_URI.to_owned() + "1.json"))
+            );
+
+            assert!(sh34
+                .set_token_uri(1, Some(String::from("ipfs://fixed.json")))
+                .is_ok());
+            assert!(sh34.lock_token_uri(1).is_ok());
+            assert!(sh34.is_token_uri_locked(1));
+            assert_eq!(
+                sh34.set_token_uri(1, None),
+                Err(PSP34Error::Custom(Shiden34Error::TokenUriLocked.as_str()))
+            );
+            assert_eq!(sh34.token_uri(1), Ok(String::from("ipfs://fixed.json")));
+
+            // Transfer and three MetadataUpdate events
+            assert_eq!(4, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn freeze_metadata_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let seed = [3u8; 32];
+            assert!(sh34
+                .set_provenance_hash([1u8; 32], seed_commitment(&seed))
+                .is_ok());
+            assert!(sh34
+                .set_placeholder_uri(String::from("ipfs://placeholder.json"))
+                .is_ok());
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            assert!(sh34.mint_next().is_ok());
+            assert!(!sh34.is_metadata_frozen());
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.freeze_metadata(),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+
+            // token URIs must be final before they are frozen
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.freeze_metadata(),
+                Err(PSP34Error::Custom(Shiden34Error::NotRevealed.as_str()))
+            );
+            assert!(sh34.reveal(BASE_URI.into()).is_ok());
+            assert_eq!(
+                sh34.freeze_metadata(),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::StartingIndexNotSet.as_str()
+                ))
+            );
+            assert!(sh34.airdrop(vec![(accounts.bob, MAX_SUPPLY - 1)]).is_ok());
+            assert!(sh34.set_starting_index(seed).is_ok());
+            let starting_index = sh34.starting_index().unwrap();
+
+            assert!(sh34.freeze_metadata().is_ok());
+            assert!(sh34.is_metadata_frozen());
+            assert_eq!(
+                sh34.freeze_metadata(),
+                Err(PSP34Error::Custom(Shiden34Error::MetadataFrozen.as_str()))
+            );
+            assert_eq!(
+                sh34.set_base_uri(String::from("ipfs://swapped/")),
+                Err(PSP34Error::Custom(Shiden34Error::MetadataFrozen.as_str()))
+            );
+            assert_eq!(
+                sh34.set_token_uri(1, Some(String::from("ipfs://swapped.json"))),
+                Err(PSP34Error::Custom(Shiden34Error::MetadataFrozen.as_str()))
+            );
+            assert_eq!(
+                sh34.set_starting_index(seed),
+                Err(PSP34Error::Custom(Shiden34Error::MetadataFrozen.as_str()))
+            );
+            assert_eq!(
+                sh34.token_uri(1),
+                Ok(BASE_URI.to_owned() + &(1 + starting_index).to_string() + ".json")
+            );
+
+            // Transfer, Revealed, Transfer for each airdropped token and PermanentUri events
+            assert_eq!(
+                MAX_SUPPLY as usize + 2,
+                ink::env::test::recorded_events().count()
+            );
+        }
+
+        #[cfg(feature = "onchain_metadata")]
+        #[ink::test]
+        fn onchain_metadata_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            assert!(sh34.mint_next().is_ok());
+            assert_eq!(
+                sh34.onchain_token_uri(1),
+                // {"name":"","description":"","image":"","attributes":[]}
+                Ok(String::from(
+                    "data:application/json;base64,eyJuYW1lIjoiIiwiZGVzY3JpcHRpb24iOiIiLCJpbWFnZSI6IiIsImF0dHJpYnV0ZXMiOltdfQ=="
+                ))
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_token_attributes(1, vec![]),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+
+            set_sender(accounts.alice);
+            assert!(sh34
+                .set_token_metadata(
+                    1,
+                    String::from("Shiden #1"),
+                    String::from("First \"one\""),
+                    String::from("data:image/svg+xml;base64,PHN2Zy8+"),
+                )
+                .is_ok());
+            assert!(sh34
+                .set_token_attributes(
+                    1,
+                    vec![
+                        (String::from("Background"), String::from("Blue")),
+                        (String::from("Eyes"), String::from("Laser")),
+                    ],
+                )
+                .is_ok());
+            assert_eq!(
+                sh34.onchain_token_uri(1),
+                Ok(String::from(
+                    "data:application/json;base64,eyJuYW1lIjoiU2hpZGVuICMxIiwiZGVzY3JpcHRpb24iOiJGaXJzdCBcIm9uZVwiIiwiaW1hZ2UiOiJkYXRhOmltYWdlL3N2Zyt4bWw7YmFzZTY0LFBITjJaeTgrIiwiYXR0cmlidXRlcyI6W3sidHJhaXRfdHlwZSI6IkJhY2tncm91bmQiLCJ2YWx1ZSI6IkJsdWUifSx7InRyYWl0X3R5cGUiOiJFeWVzIiwidmFsdWUiOiJMYXNlciJ9XX0="
+                ))
+            );
+            assert_eq!(sh34.onchain_token_uri(42), Err(TokenNotExists));
+
+            // token metadata follows the multisig like token URIs
+            assert!(sh34
+                .configure_multisig(vec![accounts.bob, accounts.charlie], 2, 100)
+                .is_ok());
+            assert_eq!(
+                sh34.set_token_metadata(1, String::new(), String::new(), String::new()),
+                Err(PSP34Error::Custom(Shiden34Error::MultisigRequired.as_str()))
+            );
+            assert_eq!(
+                sh34.set_token_attributes(1, vec![]),
+                Err(PSP34Error::Custom(Shiden34Error::MultisigRequired.as_str()))
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.propose_action(AdminAction::SetTokenAttributes(
+                    1,
+                    vec![(String::from("Eyes"), String::from("Blue"))]
+                )),
+                Ok(0)
+            );
+            set_sender(accounts.charlie);
+            assert!(sh34.approve_action(0).is_ok());
+            assert!(sh34.execute_action(0).is_ok());
+            assert_eq!(
+                metadata::PSP34MetadataImpl::get_attribute(
+                    &sh34,
+                    Id::U64(1),
+                    String::from("attributes")
+                ),
+                Some(String::from(
+                    "[{\"trait_type\":\"Eyes\",\"value\":\"Blue\"}]"
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn reveal_works() {
+            const PLACEHOLDER_URI: &str = "ipfs://placeholder.json";
+            const REVEALED_BASE_URI: &str = "ipfs://revealed/";
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            assert!(sh34.set_placeholder_uri(PLACEHOLDER_URI.into()).is_ok());
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            assert!(sh34.mint_next().is_ok());
+            assert!(!sh34.is_revealed());
+            assert_eq!(sh34.token_uri(1), Ok(String::from(PLACEHOLDER_URI)));
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.reveal(REVEALED_BASE_URI.into()),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+
+            set_sender(accounts.alice);
+            assert!(sh34.reveal(REVEALED_BASE_URI.into()).is_ok());
+            assert!(sh34.is_revealed());
+            assert_eq!(
+                sh34.token_uri(1),
+                Ok(String::from(REVEALED_BASE_URI.to_owned() + "1.json"))
+            );
+            // Transfer and Revealed events
+            assert_eq!(2, ink::env::test::recorded_events().count());
+
+            // reveal can't be repeated or undone
+            assert_eq!(
+                sh34.reveal(BASE_URI.into()),
+                Err(PSP34Error::Custom(Shiden34Error::AlreadyRevealed.as_str()))
+            );
+            assert_eq!(
+                sh34.set_placeholder_uri(PLACEHOLDER_URI.into()),
+                Err(PSP34Error::Custom(Shiden34Error::AlreadyRevealed.as_str()))
+            );
+        }
+
+        #[ink::test]
+        fn provenance_and_starting_index_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let seed = [3u8; 32];
+            assert!(sh34
+                .set_provenance_hash([1u8; 32], seed_commitment(&seed))
+                .is_ok());
+            assert_eq!(sh34.provenance_hash(), Some([1u8; 32]));
+            assert!(sh34.airdrop(vec![(accounts.bob, 1)]).is_ok());
+            assert_eq!(
+                sh34.set_provenance_hash([2u8; 32], seed_commitment(&[4u8; 32])),
+                Err(PSP34Error::Custom(Shiden34Error::ProvenanceFrozen.as_str()))
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_starting_index(seed),
+                Err(PSP34Error::Custom(Shiden34Error::SaleNotEnded.as_str()))
+            );
+            set_sender(accounts.alice);
+            assert!(sh34.airdrop(vec![(accounts.bob, MAX_SUPPLY - 1)]).is_ok());
+
+            // anyone can set the starting index from the committed seed once the collection
+            // is sold out, and the offset doesn't depend on when it is called
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_starting_index([4u8; 32]),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::InvalidStartingIndexSeed.as_str()
+                ))
+            );
+            test::advance_block::<ink::env::DefaultEnvironment>();
+            assert!(sh34.set_starting_index(seed).is_ok());
+            let starting_index = sh34.starting_index().unwrap();
+            assert!(starting_index > 0 && starting_index < MAX_SUPPLY);
+            let mut entropy = [0u8; 32];
+            ink::env::hash_encoded::<ink::env::hash::Keccak256, _>(
+                &(seed, Some([1u8; 32])),
+                &mut entropy,
+            );
+            let mut index_bytes = [0u8; 8];
+            index_bytes.copy_from_slice(&entropy[..8]);
+            assert_eq!(
+                starting_index,
+                u64::from_le_bytes(index_bytes) % (MAX_SUPPLY - 1) + 1
+            );
+            assert_eq!(
+                sh34.token_uri(1),
+                Ok(BASE_URI.to_owned() + &(1 + starting_index).to_string() + ".json")
+            );
+            // the token shifted past max supply wraps around to the first file
+            assert_eq!(
+                sh34.token_uri(MAX_SUPPLY - starting_index + 1),
+                Ok(BASE_URI.to_owned() + "1.json")
+            );
+            assert_eq!(
+                sh34.token_uri(MAX_SUPPLY - starting_index),
+                Ok(BASE_URI.to_owned() + &MAX_SUPPLY.to_string() + ".json")
+            );
+            // every file in 1..=max_supply is served exactly once
+            let mut uris: Vec<String> = (1..=MAX_SUPPLY)
+                .map(|token_id| sh34.token_uri(token_id).unwrap())
+                .collect();
+            uris.sort();
+            let mut expected: Vec<String> = (1..=MAX_SUPPLY)
+                .map(|id| BASE_URI.to_owned() + &id.to_string() + ".json")
+                .collect();
+            expected.sort();
+            assert_eq!(uris, expected);
+            assert_eq!(
+                sh34.set_starting_index(seed),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::StartingIndexAlreadySet.as_str()
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn owner_is_set() {
+            let accounts = default_accounts();
+            let sh34 = init();
+            assert_eq!(Ownable::owner(&sh34).unwrap(), accounts.alice);
+        }
+
+        #[ink::test]
+        fn set_base_uri_works() {
+            let accounts = default_accounts();
+            const NEW_BASE_URI: &str = "new_uri/";
+            let mut sh34 = init();
+
+            set_sender(accounts.alice);
+            let collection_id = PSP34Impl::collection_id(&sh34);
+            assert!(sh34.set_base_uri(NEW_BASE_URI.into()).is_ok());
+            assert_eq!(
+                PSP34MetadataImpl::get_attribute(&sh34, collection_id, String::from("baseUri")),
+                Some(String::from(NEW_BASE_URI))
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_base_uri(NEW_BASE_URI.into()),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+        }
+
+        #[ink::test]
+        fn check_supply_overflow_ok() {
+            let max_supply = u64::MAX - 1;
+            let mut sh34 = Shiden34Contract::new(
+                String::from("Shiden34"),
+                String::from("SH34"),
+                String::from(BASE_URI),
+                max_supply,
+                PRICE,
+            );
+            sh34.payable_mint.last_token_id = max_supply - 1;
+
+            // check case when last_token_id.add(mint_amount) if more than u64::MAX
+            assert!(sh34.set_max_mint_amount(u64::MAX).is_ok());
+            assert_eq!(
+                sh34.check_amount(3),
+                Err(PSP34Error::Custom(Shiden34Error::CollectionIsFull.as_str()))
+            );
+
+            // check case when mint_amount is 0
+            assert_eq!(
+                sh34.check_amount(0),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::CannotMintZeroTokens.as_str()
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn set_max_supply_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            assert!(sh34.airdrop(vec![(accounts.bob, 3)]).is_ok());
+
+            // max supply can only go down, and not below minted tokens
+            assert_eq!(
+                sh34.set_max_supply(MAX_SUPPLY + 1),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidMaxSupply.as_str()))
+            );
+            assert_eq!(
+                sh34.set_max_supply(2),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidMaxSupply.as_str()))
+            );
+            assert!(sh34.set_max_supply(5).is_ok());
+            assert_eq!(sh34.max_supply(), 5);
+            assert_eq!(
+                sh34.airdrop(vec![(accounts.bob, 3)]),
+                Err(PSP34Error::Custom(Shiden34Error::CollectionIsFull.as_str()))
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.close_collection(),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+            set_sender(accounts.alice);
+            assert!(sh34.close_collection().is_ok());
+            assert!(sh34.is_collection_closed());
+            assert_eq!(sh34.max_supply(), 3);
+            assert_eq!(
+                sh34.set_max_supply(3),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::CollectionAlreadyClosed.as_str()
+                ))
+            );
+            set_sender(accounts.bob);
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            assert_eq!(
+                sh34.mint_next(),
+                Err(PSP34Error::Custom(Shiden34Error::CollectionIsFull.as_str()))
+            );
+            // 3 Transfer, MaxSupplyChanged and CollectionClosed events
+            assert_eq!(5, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn check_value_overflow_ok() {
+            let max_supply = u64::MAX;
+            let price = u128::MAX as u128;
             let sh34 = Shiden34Contract::new(
                 String::from("Shiden34"),
                 String::from("SH34"),
                 String::from(BASE_URI),
-                max_supply,
-                price,
+                max_supply,
+                price,
+            );
+            let transferred_value = u128::MAX;
+            let mint_amount = u64::MAX;
+            assert_eq!(
+                sh34.check_value(transferred_value, mint_amount),
+                Err(PSP34Error::Custom(Shiden34Error::BadMintValue.as_str()))
+            );
+        }
+
+        #[ink::test]
+        fn allowlist_mint_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            let bob_leaf = allowlist_leaf(accounts.bob, 2);
+            let charlie_leaf = allowlist_leaf(accounts.charlie, 1);
+            set_sender(accounts.alice);
+            assert!(sh34
+                .set_allowlist_root(Some(hash_pair(&bob_leaf, &charlie_leaf)))
+                .is_ok());
+            assert!(sh34.set_max_mint_amount(2).is_ok());
+
+            // public mint is closed while the allowlist is active
+            set_sender(accounts.bob);
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            assert_eq!(
+                sh34.mint_next(),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::PublicMintNotActive.as_str()
+                ))
+            );
+
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE * 2);
+            assert!(sh34.allowlist_mint(2, 2, vec![charlie_leaf]).is_ok());
+            assert_eq!(PSP34Impl::balance_of(&sh34, accounts.bob), 2);
+            assert_eq!(sh34.allowlist_minted(accounts.bob), 2);
+
+            // Bob has used up his allocation and can't claim a bigger one
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            assert_eq!(
+                sh34.allowlist_mint(1, 2, vec![charlie_leaf]),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::AllocationExhausted.as_str()
+                ))
+            );
+            assert_eq!(
+                sh34.allowlist_mint(1, 3, vec![charlie_leaf]),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::InvalidMerkleProof.as_str()
+                ))
+            );
+
+            set_sender(accounts.charlie);
+            assert!(sh34.allowlist_mint(1, 1, vec![bob_leaf]).is_ok());
+            assert_eq!(
+                PSP34Impl::owner_of(&sh34, Id::U64(3)),
+                Some(accounts.charlie)
+            );
+
+            // public mint opens once the allowlist is removed
+            set_sender(accounts.alice);
+            assert!(sh34.set_allowlist_root(None).is_ok());
+            set_sender(accounts.bob);
+            assert!(sh34.mint_next().is_ok());
+        }
+
+        fn allowlist_leaf(account: AccountId, allocation: u64) -> [u8; 32] {
+            let mut leaf = [0u8; 32];
+            ink::env::hash_encoded::<ink::env::hash::Keccak256, _>(
+                &(account, allocation),
+                &mut leaf,
+            );
+            leaf
+        }
+
+        #[ink::test]
+        fn sale_phases_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            assert!(sh34.set_max_mint_amount(3).is_ok());
+            assert_eq!(
+                sh34.add_sale_phase(sale_phase(PRICE / 2, 3, 2, 100, 100)),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidSalePhase.as_str()))
+            );
+            assert!(sh34
+                .add_sale_phase(sale_phase(PRICE / 2, 3, 2, 100, 200))
+                .is_ok());
+            assert!(sh34
+                .add_sale_phase(sale_phase(PRICE, MAX_SUPPLY, MAX_SUPPLY, 200, 300))
+                .is_ok());
+
+            // no phase is running yet
+            set_sender(accounts.bob);
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            assert_eq!(sh34.current_sale_phase(), None);
+            assert_eq!(
+                sh34.mint_next(),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::NoActiveSalePhase.as_str()
+                ))
+            );
+
+            // first phase has its own price, wallet limit and supply
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(sh34.price(), PRICE / 2);
+            assert!(sh34.mint(accounts.bob, 2).is_ok());
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE / 2);
+            assert_eq!(
+                sh34.mint_next(),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::WalletLimitReached.as_str()
+                ))
+            );
+            set_sender(accounts.charlie);
+            assert!(sh34.mint_next().is_ok());
+            assert_eq!(
+                sh34.current_sale_phase()
+                    .map(|(phase_id, _, remaining)| (phase_id, remaining)),
+                Some((0, 0))
+            );
+            set_sender(accounts.django);
+            assert_eq!(
+                sh34.mint_next(),
+                Err(PSP34Error::Custom(Shiden34Error::SalePhaseSoldOut.as_str()))
+            );
+
+            // second phase is limited by the remaining collection supply
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(200);
+            assert_eq!(
+                sh34.current_sale_phase()
+                    .map(|(phase_id, _, remaining)| (phase_id, remaining)),
+                Some((1, MAX_SUPPLY - 3))
+            );
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            assert!(sh34.mint_next().is_ok());
+            assert_eq!(PSP34Impl::total_supply(&sh34), 4);
+        }
+
+        #[ink::test]
+        fn wallet_limit_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            assert!(sh34.set_max_mint_amount(3).is_ok());
+            assert!(sh34.set_max_per_wallet(Some(2)).is_ok());
+            assert_eq!(sh34.wallet_mints_remaining(accounts.bob), Some(2));
+
+            // repeated calls are limited over the wallet lifetime
+            set_sender(accounts.bob);
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            assert!(sh34.mint_next().is_ok());
+            assert!(sh34.mint(accounts.bob, 1).is_ok());
+            assert_eq!(
+                sh34.mint_next(),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::WalletLimitReached.as_str()
+                ))
+            );
+            assert_eq!(sh34.wallet_minted(accounts.bob), 2);
+            assert_eq!(sh34.wallet_mints_remaining(accounts.bob), Some(0));
+
+            // recipient is counted only when configured
+            set_sender(accounts.charlie);
+            assert!(sh34.mint(accounts.bob, 1).is_ok());
+            assert_eq!(sh34.wallet_minted(accounts.bob), 2);
+            set_sender(accounts.alice);
+            assert!(sh34.set_wallet_limit_counts_recipient(true).is_ok());
+            set_sender(accounts.charlie);
+            assert_eq!(
+                sh34.mint(accounts.bob, 1),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::WalletLimitReached.as_str()
+                ))
+            );
+            assert!(sh34.mint(accounts.django, 1).is_ok());
+            assert_eq!(sh34.wallet_minted(accounts.charlie), 2);
+            assert_eq!(sh34.wallet_minted(accounts.django), 1);
+
+            // limit can be lifted
+            set_sender(accounts.alice);
+            assert!(sh34.set_max_per_wallet(None).is_ok());
+            assert_eq!(sh34.wallet_mints_remaining(accounts.bob), None);
+            set_sender(accounts.bob);
+            assert!(sh34.mint_next().is_ok());
+        }
+
+        #[ink::test]
+        fn dutch_auction_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            let auction = DutchAuction {
+                start_price: PRICE * 3,
+                floor_price: PRICE,
+                price_step: PRICE,
+                step_interval: 10,
+                start: 100,
+                rebate: true,
+            };
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.set_dutch_auction(Some(DutchAuction {
+                    step_interval: 0,
+                    ..auction.clone()
+                })),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidAuction.as_str()))
+            );
+            assert!(sh34.set_dutch_auction(Some(auction)).is_ok());
+
+            set_sender(accounts.bob);
+            set_balance(accounts.bob, PRICE * 3);
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE * 3);
+            assert_eq!(
+                sh34.mint_next(),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::AuctionNotStarted.as_str()
+                ))
+            );
+
+            // price decays every interval down to the floor
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(105);
+            assert_eq!(sh34.price(), PRICE * 3);
+            assert!(pay_with_call!(sh34.mint_next(), PRICE * 3).is_ok());
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(110);
+            assert_eq!(sh34.price(), PRICE * 2);
+            set_sender(accounts.charlie);
+            set_balance(accounts.charlie, PRICE * 2);
+            assert!(pay_with_call!(sh34.mint_next(), PRICE * 2).is_ok());
+
+            // rebates are claimable once the floor is reached
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.claim_auction_rebate(),
+                Err(PSP34Error::Custom(Shiden34Error::AuctionNotEnded.as_str()))
+            );
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+            assert_eq!(sh34.price(), PRICE);
+            assert_eq!(sh34.auction_rebate(accounts.bob), PRICE);
+            assert_eq!(sh34.auction_rebate(accounts.charlie), 0);
+            assert!(sh34.claim_auction_rebate().is_ok());
+            assert_eq!(
+                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.bob),
+                Ok(PRICE)
+            );
+            assert_eq!(
+                sh34.claim_auction_rebate(),
+                Err(PSP34Error::Custom(Shiden34Error::NoRebateToClaim.as_str()))
+            );
+
+            // auction settings are locked once it has started
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.set_dutch_auction(None),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::AuctionAlreadyStarted.as_str()
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn pause_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.pause(),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+
+            set_sender(accounts.alice);
+            assert!(sh34.pause().is_ok());
+            assert!(sh34.paused());
+            assert_eq!(
+                sh34.pause(),
+                Err(PSP34Error::Custom(Shiden34Error::MintingPaused.as_str()))
+            );
+            assert_eq!(1, ink::env::test::recorded_events().count());
+
+            set_sender(accounts.bob);
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            assert_eq!(
+                sh34.mint_next(),
+                Err(PSP34Error::Custom(Shiden34Error::MintingPaused.as_str()))
+            );
+            assert_eq!(
+                sh34.mint(accounts.bob, 1),
+                Err(PSP34Error::Custom(Shiden34Error::MintingPaused.as_str()))
+            );
+
+            set_sender(accounts.alice);
+            assert!(sh34.unpause().is_ok());
+            assert!(!sh34.paused());
+            assert_eq!(
+                sh34.unpause(),
+                Err(PSP34Error::Custom(Shiden34Error::MintingNotPaused.as_str()))
+            );
+            assert_eq!(2, ink::env::test::recorded_events().count());
+
+            set_sender(accounts.bob);
+            assert!(sh34.mint_next().is_ok());
+        }
+
+        #[ink::test]
+        fn airdrop_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.airdrop(vec![(accounts.bob, 1)]),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.airdrop(vec![(accounts.bob, MAX_AIRDROP_AMOUNT + 1)]),
+                Err(PSP34Error::Custom(Shiden34Error::AirdropTooLarge.as_str()))
+            );
+            assert_eq!(
+                sh34.airdrop(vec![(accounts.bob, MAX_SUPPLY), (accounts.charlie, 1)]),
+                Err(PSP34Error::Custom(Shiden34Error::CollectionIsFull.as_str()))
+            );
+            assert_eq!(
+                sh34.airdrop(vec![]),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::CannotMintZeroTokens.as_str()
+                ))
+            );
+
+            assert!(sh34
+                .airdrop(vec![(accounts.bob, 2), (accounts.charlie, 1)])
+                .is_ok());
+            assert_eq!(PSP34Impl::balance_of(&sh34, accounts.bob), 2);
+            assert_eq!(
+                PSP34Impl::owner_of(&sh34, Id::U64(3)),
+                Some(accounts.charlie)
+            );
+            assert_eq!(sh34.payable_mint.last_token_id, 3);
+            assert_eq!(3, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn random_ids_works() {
+            let accounts = default_accounts();
+            let seed = [7u8; 32];
+            let mut sh34 = Shiden34Contract::new_with_random_ids(
+                String::from("Shiden34"),
+                String::from("SH34"),
+                String::from(BASE_URI),
+                MAX_SUPPLY,
+                PRICE,
+                seed_commitment(&seed),
             );
-            let transferred_value = u128::MAX;
-            let mint_amount = u64::MAX;
+            assert_eq!(sh34.id_assignment(), IdAssignment::Random);
+
+            set_sender(accounts.alice);
+            // lowering max supply would change the shuffle of already minted tokens
             assert_eq!(
-                sh34.check_value(transferred_value, mint_amount),
+                sh34.set_max_supply(MAX_SUPPLY / 2),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::MaxSupplyFixedForRandomIds.as_str()
+                ))
+            );
+            assert!(sh34.airdrop(vec![(accounts.bob, MAX_SUPPLY - 1)]).is_ok());
+            assert_eq!(
+                sh34.close_collection(),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::MaxSupplyFixedForRandomIds.as_str()
+                ))
+            );
+            assert_eq!(sh34.max_supply(), MAX_SUPPLY);
+            assert_eq!(
+                sh34.token_uri(1),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::RandomSeedNotRevealed.as_str()
+                ))
+            );
+            assert_eq!(
+                sh34.freeze_metadata(),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::RandomSeedNotRevealed.as_str()
+                ))
+            );
+
+            // the seed is revealed only once minting is over
+            assert_eq!(
+                sh34.reveal_random_seed(seed),
+                Err(PSP34Error::Custom(Shiden34Error::SaleNotEnded.as_str()))
+            );
+            assert!(sh34
+                .add_sale_phase(sale_phase(PRICE, MAX_SUPPLY, MAX_SUPPLY, 0, 10))
+                .is_ok());
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(10);
+            assert_eq!(
+                sh34.reveal_random_seed([8u8; 32]),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::InvalidRandomSeed.as_str()
+                ))
+            );
+            assert!(sh34.reveal_random_seed(seed).is_ok());
+            assert_eq!(sh34.random_seed(), Some(seed));
+            assert_eq!(
+                sh34.airdrop(vec![(accounts.bob, 1)]),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::RandomSeedAlreadyRevealed.as_str()
+                ))
+            );
+
+            // minted tokens get distinct metadata from 1..=max_supply in shuffled order
+            let metadata_ids: Vec<u64> = (1..MAX_SUPPLY)
+                .map(|token_id| shuffled_id(&seed, token_id, MAX_SUPPLY))
+                .collect();
+            for (token_id, metadata_id) in (1..MAX_SUPPLY).zip(metadata_ids.iter()) {
+                assert_eq!(
+                    sh34.token_uri(token_id),
+                    Ok(BASE_URI.to_owned() + &metadata_id.to_string() + ".json")
+                );
+            }
+            assert_ne!(metadata_ids, (1..MAX_SUPPLY).collect::<Vec<u64>>());
+            let mut all_ids: Vec<u64> = (1..=MAX_SUPPLY)
+                .map(|token_id| shuffled_id(&seed, token_id, MAX_SUPPLY))
+                .collect();
+            all_ids.sort();
+            assert_eq!(all_ids, (1..=MAX_SUPPLY).collect::<Vec<u64>>());
+            assert!(sh34.freeze_metadata().is_ok());
+        }
+
+        #[ink::test]
+        fn psp22_payment_config_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            let token = AccountId::from([0x42; 32]);
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_psp22_payment(Some(token), PRICE),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+            assert_eq!(
+                sh34.withdraw_psp22(token),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_psp22_payment(Some(token), PRICE / 2).is_ok());
+            assert_eq!(sh34.psp22_payment(), Some((token, PRICE / 2)));
+
+            // native payment keeps working next to PSP22 payment
+            set_sender(accounts.bob);
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            assert!(sh34.mint_next().is_ok());
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE / 2);
+            assert_eq!(
+                sh34.mint_next(),
+                Err(PSP34Error::Custom(Shiden34Error::BadMintValue.as_str()))
+            );
+
+            // mints without transferred value take the PSP22 branch,
+            // which is refused while sale phases set native prices
+            set_sender(accounts.alice);
+            assert!(sh34
+                .add_sale_phase(sale_phase(PRICE / 2, 3, 2, 0, 100))
+                .is_ok());
+            set_sender(accounts.bob);
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            assert_eq!(
+                sh34.mint_next(),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::Psp22PaymentUnavailable.as_str()
+                ))
+            );
+            // native payment follows the phase price
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE / 2);
+            assert!(sh34.mint_next().is_ok());
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_psp22_payment(None, 0).is_ok());
+            assert_eq!(sh34.psp22_payment(), None);
+            // without a PSP22 token zero value is a bad native payment
+            set_sender(accounts.bob);
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            assert_eq!(
+                sh34.mint_next(),
+                Err(PSP34Error::Custom(Shiden34Error::BadMintValue.as_str()))
+            );
+        }
+
+        #[ink::test]
+        fn refund_excess_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+            set_balance(accounts.bob, PRICE * 3);
+
+            // exact value is required by default
+            assert!(!sh34.refund_excess());
+            assert_eq!(
+                pay_with_call!(sh34.mint_next(), PRICE + 1),
+                Err(PSP34Error::Custom(Shiden34Error::BadMintValue.as_str()))
+            );
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_refund_excess(true).is_ok());
+            set_sender(accounts.bob);
+            set_balance(accounts.bob, PRICE * 3);
+            assert!(pay_with_call!(sh34.mint_next(), PRICE * 2).is_ok());
+            assert_eq!(
+                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.bob),
+                Ok(PRICE * 2)
+            );
+            // Transfer and Refunded events
+            assert_eq!(2, ink::env::test::recorded_events().count());
+
+            assert_eq!(
+                pay_with_call!(sh34.mint_next(), PRICE - 1),
                 Err(PSP34Error::Custom(Shiden34Error::BadMintValue.as_str()))
             );
         }
 
+        #[ink::test]
+        fn mint_with_voucher_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            let secret_key = secp256k1::SecretKey::from_slice(&VOUCHER_SIGNER_KEY).unwrap();
+            let signer = secp256k1::PublicKey::from_secret_key(secp256k1::SECP256K1, &secret_key);
+            let price = PRICE / 2;
+            let signature = sign_voucher(accounts.bob, 2, price, 1, 1000);
+            set_sender(accounts.alice);
+            assert!(sh34.set_max_mint_amount(2).is_ok());
+
+            // vouchers are disabled until the signer is set
+            set_sender(accounts.bob);
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(price * 2);
+            assert_eq!(
+                sh34.mint_with_voucher(accounts.bob, 2, price, 1, 1000, signature),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::InvalidVoucherSignature.as_str()
+                ))
+            );
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_voucher_signer(Some(signer.serialize())).is_ok());
+
+            // voucher terms can't be altered
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.mint_with_voucher(accounts.bob, 2, price / 2, 1, 1000, signature),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::InvalidVoucherSignature.as_str()
+                ))
+            );
+            assert_eq!(
+                sh34.mint_with_voucher(accounts.charlie, 2, price, 1, 1000, signature),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::InvalidVoucherSignature.as_str()
+                ))
+            );
+
+            assert!(sh34
+                .mint_with_voucher(accounts.bob, 2, price, 1, 1000, signature)
+                .is_ok());
+            assert_eq!(PSP34Impl::balance_of(&sh34, accounts.bob), 2);
+            assert!(sh34.is_voucher_used(1));
+
+            // voucher can't be replayed
+            assert_eq!(
+                sh34.mint_with_voucher(accounts.bob, 2, price, 1, 1000, signature),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::VoucherAlreadyUsed.as_str()
+                ))
+            );
+
+            // voucher expires
+            let signature = sign_voucher(accounts.bob, 1, price, 2, 1000);
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(1001);
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(price);
+            assert_eq!(
+                sh34.mint_with_voucher(accounts.bob, 1, price, 2, 1000, signature),
+                Err(PSP34Error::Custom(Shiden34Error::VoucherExpired.as_str()))
+            );
+
+            // vouchers count towards the per-wallet limit
+            set_sender(accounts.alice);
+            assert!(sh34.set_max_per_wallet(Some(3)).is_ok());
+            set_sender(accounts.bob);
+            let signature = sign_voucher(accounts.bob, 2, price, 3, 2000);
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(price * 2);
+            assert_eq!(
+                sh34.mint_with_voucher(accounts.bob, 2, price, 3, 2000, signature),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::WalletLimitReached.as_str()
+                ))
+            );
+            assert!(!sh34.is_voucher_used(3));
+        }
+
+        fn sign_voucher(
+            to: AccountId,
+            mint_amount: u64,
+            price_per_mint: Balance,
+            nonce: u64,
+            expiry: u64,
+        ) -> [u8; 65] {
+            let mut message_hash = [0u8; 32];
+            ink::env::hash_encoded::<ink::env::hash::Keccak256, _>(
+                &(
+                    test::callee::<ink::env::DefaultEnvironment>(),
+                    to,
+                    mint_amount,
+                    price_per_mint,
+                    nonce,
+                    expiry,
+                ),
+                &mut message_hash,
+            );
+            let secret_key = secp256k1::SecretKey::from_slice(&VOUCHER_SIGNER_KEY).unwrap();
+            let message = secp256k1::Message::from_slice(&message_hash).unwrap();
+            let (recovery_id, compact) = secp256k1::SECP256K1
+                .sign_ecdsa_recoverable(&message, &secret_key)
+                .serialize_compact();
+
+            let mut signature = [0u8; 65];
+            signature[..64].copy_from_slice(&compact);
+            signature[64] = recovery_id.to_i32() as u8;
+            signature
+        }
+
+        fn seed_commitment(seed: &[u8; 32]) -> [u8; 32] {
+            let mut commitment = [0u8; 32];
+            ink::env::hash_bytes::<ink::env::hash::Keccak256>(seed, &mut commitment);
+            commitment
+        }
+
+        fn sale_phase(
+            price_per_mint: Balance,
+            max_supply: u64,
+            max_per_wallet: u64,
+            start: u64,
+            end: u64,
+        ) -> SalePhase {
+            SalePhase {
+                name: String::from("phase"),
+                price_per_mint,
+                max_supply,
+                max_per_wallet,
+                start,
+                end,
+            }
+        }
+
         fn default_accounts() -> test::DefaultAccounts<ink::env::DefaultEnvironment> {
             test::default_accounts::<Environment>()
         }
@@ -1 +1,6 @@
+pub mod multisig;
+#[cfg(feature = "onchain_metadata")]
+pub mod onchain_metadata;
 pub mod payable_mint;
+pub mod payment_splitter;
+pub mod royalty;
@@ -0,0 +1,2 @@
+pub mod multisig;
+pub mod types;
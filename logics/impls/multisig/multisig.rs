@@ -0,0 +1,341 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use ink::prelude::vec::Vec;
+
+#[cfg(feature = "onchain_metadata")]
+use crate::impls::onchain_metadata;
+use crate::impls::{
+    multisig::types::{
+        AdminAction,
+        Data,
+        Proposal,
+    },
+    payable_mint,
+    payable_mint::types::{
+        Shiden34Error,
+        TimelockOperation,
+        ADMIN,
+    },
+    payment_splitter,
+};
+use openbrush::{
+    contracts::{
+        access_control,
+        access_control::only_role,
+        psp34::PSP34Error,
+        reentrancy_guard,
+        reentrancy_guard::non_reentrant,
+    },
+    modifiers,
+    traits::{
+        AccountId,
+        Storage,
+        Timestamp,
+    },
+};
+
+#[openbrush::trait_definition]
+pub trait MultisigImpl:
+    Storage<Data>
+    + Storage<reentrancy_guard::Data>
+    + Storage<access_control::Data>
+    + payable_mint::payable_mint::Internal
+    + payment_splitter::payment_splitter::Internal
+    + Internal
+{
+    /// Hand admin actions over to `signers`, of which `threshold` must approve each action.
+    /// Once configured, signers can only be changed through an approved action.
+    /// Operations queued by the timelock before are cancelled
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn configure_multisig(
+        &mut self,
+        signers: Vec<AccountId>,
+        threshold: u32,
+        proposal_lifetime: Timestamp,
+    ) -> Result<(), PSP34Error> {
+        if !self.data::<Data>().signers.is_empty() {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::MultisigAlreadyConfigured.as_str(),
+            ))
+        }
+        if proposal_lifetime == 0 {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::InvalidMultisigConfig.as_str(),
+            ))
+        }
+        self.set_signers(signers, threshold)?;
+        self.data::<Data>().proposal_lifetime = proposal_lifetime;
+        self.cancel_pending_operations();
+
+        Ok(())
+    }
+
+    /// Propose admin action, approved by the proposer. Returns proposal id
+    #[ink(message)]
+    fn propose_action(&mut self, action: AdminAction) -> Result<u32, PSP34Error> {
+        let caller = Self::env().caller();
+        self.check_signer(caller)?;
+        let proposal_id = self.data::<Data>().proposal_count;
+        let expires_at = Self::env()
+            .block_timestamp()
+            .saturating_add(self.data::<Data>().proposal_lifetime);
+        self.data::<Data>().proposals.insert(
+            &proposal_id,
+            &Proposal {
+                action,
+                proposer: caller,
+                expires_at,
+                executed: false,
+            },
+        );
+        self.data::<Data>()
+            .approvals
+            .insert(&(proposal_id, caller), &true);
+        self.data::<Data>().proposal_count = proposal_id + 1;
+        self._emit_action_proposed_event(proposal_id, caller, expires_at);
+
+        Ok(proposal_id)
+    }
+
+    /// Approve pending admin action
+    #[ink(message)]
+    fn approve_action(&mut self, proposal_id: u32) -> Result<(), PSP34Error> {
+        let caller = Self::env().caller();
+        self.check_signer(caller)?;
+        self.pending_proposal(proposal_id)?;
+        if self.has_approved(proposal_id, caller) {
+            return Err(PSP34Error::Custom(Shiden34Error::AlreadyApproved.as_str()))
+        }
+        self.data::<Data>()
+            .approvals
+            .insert(&(proposal_id, caller), &true);
+        self._emit_action_approved_event(proposal_id, caller);
+
+        Ok(())
+    }
+
+    /// Execute admin action approved by enough signers. Withdrawals, new payees, reveal, baseUri
+    /// and max mint amount changes are queued while the timelock delay is set
+    #[ink(message)]
+    #[modifiers(non_reentrant)]
+    fn execute_action(&mut self, proposal_id: u32) -> Result<(), PSP34Error> {
+        self.check_signer(Self::env().caller())?;
+        let mut proposal = self.pending_proposal(proposal_id)?;
+        if self.approval_count(proposal_id) < self.data::<Data>().threshold {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::ThresholdNotReached.as_str(),
+            ))
+        }
+        proposal.executed = true;
+        self.data::<Data>()
+            .proposals
+            .insert(&proposal_id, &proposal);
+
+        let timelocked = self.data::<payable_mint::types::Data>().timelock_delay > 0;
+        match proposal.action {
+            AdminAction::Withdraw if timelocked => {
+                let recipient = self.withdrawal_recipient()?;
+                self.queue_operation(TimelockOperation::Withdraw(recipient))?
+            }
+            AdminAction::WithdrawTo(recipient, amount) if timelocked => {
+                self.queue_operation(TimelockOperation::WithdrawTo(recipient, amount))?
+            }
+            AdminAction::SetBaseUri(uri) if timelocked => {
+                self.queue_operation(TimelockOperation::SetBaseUri(uri))?
+            }
+            AdminAction::Reveal(base_uri) if timelocked => {
+                self.queue_operation(TimelockOperation::Reveal(base_uri))?
+            }
+            AdminAction::SetMaxMintAmount(max_amount) if timelocked => {
+                self.queue_operation(TimelockOperation::SetMaxMintAmount(max_amount))?
+            }
+            AdminAction::WithdrawPsp22(token) if timelocked => {
+                let recipient = self.withdrawal_recipient()?;
+                self.queue_operation(TimelockOperation::WithdrawPsp22(token, recipient))?
+            }
+            AdminAction::AddPayee(account, shares) if timelocked => {
+                self.queue_operation(TimelockOperation::AddPayee(account, shares))?
+            }
+            AdminAction::Withdraw => self.withdraw_balance()?,
+            AdminAction::WithdrawTo(recipient, amount) => {
+                self.withdraw_balance_to(recipient, amount)?
+            }
+            AdminAction::WithdrawPsp22(token) => self.withdraw_psp22_balance(token)?,
+            AdminAction::SetTreasury(treasury) => {
+                self.data::<payable_mint::types::Data>().treasury = treasury
+            }
+            AdminAction::SetBaseUri(uri) => self.update_base_uri(uri)?,
+            AdminAction::Reveal(base_uri) => self.reveal_base_uri(base_uri)?,
+            AdminAction::SetTokenUri(token_id, uri) => self.update_token_uri(token_id, uri)?,
+            AdminAction::SetPrice(price_per_mint) => self.update_price(price_per_mint)?,
+            AdminAction::LockPrice => self.data::<payable_mint::types::Data>().price_locked = true,
+            AdminAction::SetMaxSupply(max_supply) => self.update_max_supply(max_supply)?,
+            AdminAction::CloseCollection => self.close_supply()?,
+            AdminAction::SetMaxMintAmount(max_amount) => {
+                self.data::<payable_mint::types::Data>().max_amount = max_amount
+            }
+            AdminAction::AddPayee(account, shares) => self._add_payee(account, shares)?,
+            AdminAction::SetSigners(signers, threshold) => self.set_signers(signers, threshold)?,
+            AdminAction::SetReferralBps(referral_bps) => self.update_referral_bps(referral_bps)?,
+            AdminAction::AddSalePhase(phase) => self.append_sale_phase(phase)?,
+            AdminAction::UpdateSalePhase(phase_id, phase) => {
+                self.replace_sale_phase(phase_id, phase)?
+            }
+            AdminAction::SetDutchAuction(auction) => self.update_dutch_auction(auction)?,
+            AdminAction::SetPsp22Payment(token, price_per_mint) => {
+                self.data::<payable_mint::types::Data>().psp22_token = token;
+                self.data::<payable_mint::types::Data>()
+                    .psp22_price_per_mint = price_per_mint;
+            }
+            AdminAction::SetVoucherSigner(signer) => {
+                self.data::<payable_mint::types::Data>().voucher_signer = signer
+            }
+            #[cfg(feature = "onchain_metadata")]
+            AdminAction::SetTokenMetadata(token_id, name, description, image) => {
+                onchain_metadata::onchain_metadata::Internal::update_token_metadata(
+                    self,
+                    token_id,
+                    name,
+                    description,
+                    image,
+                )?
+            }
+            #[cfg(feature = "onchain_metadata")]
+            AdminAction::SetTokenAttributes(token_id, attributes) => {
+                onchain_metadata::onchain_metadata::Internal::update_token_attributes(
+                    self, token_id, attributes,
+                )?
+            }
+        }
+        self._emit_action_executed_event(proposal_id);
+
+        Ok(())
+    }
+
+    /// Get accounts approving admin actions
+    #[ink(message)]
+    fn multisig_signers(&self) -> Vec<AccountId> {
+        self.data::<Data>().signers.clone()
+    }
+
+    /// Get number of approvals required to execute admin action
+    #[ink(message)]
+    fn multisig_threshold(&self) -> u32 {
+        self.data::<Data>().threshold
+    }
+
+    /// Get proposed admin action
+    #[ink(message)]
+    fn proposal(&self, proposal_id: u32) -> Option<Proposal> {
+        self.data::<Data>().proposals.get(&proposal_id)
+    }
+
+    /// Get number of current signers who approved the admin action
+    #[ink(message)]
+    fn approval_count(&self, proposal_id: u32) -> u32 {
+        self.data::<Data>()
+            .signers
+            .iter()
+            .filter(|signer| self.has_approved(proposal_id, **signer))
+            .count() as u32
+    }
+
+    /// Check if the signer approved the admin action
+    #[ink(message)]
+    fn has_approved(&self, proposal_id: u32, signer: AccountId) -> bool {
+        self.data::<Data>()
+            .approvals
+            .get(&(proposal_id, signer))
+            .unwrap_or(false)
+    }
+}
+
+pub trait Internal: Storage<Data> {
+    /// Check if admin actions are not handed over to signers
+    fn check_multisig_inactive(&self) -> Result<(), PSP34Error> {
+        if !self.data::<Data>().signers.is_empty() {
+            return Err(PSP34Error::Custom(Shiden34Error::MultisigRequired.as_str()))
+        }
+        Ok(())
+    }
+
+    /// Check if the account is one of signers
+    fn check_signer(&self, account: AccountId) -> Result<(), PSP34Error> {
+        if !self.data::<Data>().signers.contains(&account) {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::NotMultisigSigner.as_str(),
+            ))
+        }
+        Ok(())
+    }
+
+    /// Get proposal which is neither executed nor expired
+    fn pending_proposal(&self, proposal_id: u32) -> Result<Proposal, PSP34Error> {
+        let proposal = self
+            .data::<Data>()
+            .proposals
+            .get(&proposal_id)
+            .ok_or(PSP34Error::Custom(Shiden34Error::UnknownProposal.as_str()))?;
+        if proposal.executed {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::ProposalAlreadyExecuted.as_str(),
+            ))
+        }
+        if Self::env().block_timestamp() > proposal.expires_at {
+            return Err(PSP34Error::Custom(Shiden34Error::ProposalExpired.as_str()))
+        }
+        Ok(proposal)
+    }
+
+    /// Replace signers and number of approvals required. Signers must be unique
+    fn set_signers(&mut self, signers: Vec<AccountId>, threshold: u32) -> Result<(), PSP34Error> {
+        let has_duplicates = signers
+            .iter()
+            .enumerate()
+            .any(|(i, signer)| signers[..i].contains(signer));
+        if threshold == 0 || threshold as usize > signers.len() || has_duplicates {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::InvalidMultisigConfig.as_str(),
+            ))
+        }
+        self.data::<Data>().signers = signers;
+        self.data::<Data>().threshold = threshold;
+
+        Ok(())
+    }
+
+    /// Emit event when admin action is proposed
+    fn _emit_action_proposed_event(
+        &self,
+        _proposal_id: u32,
+        _proposer: AccountId,
+        _expires_at: Timestamp,
+    ) {
+    }
+
+    /// Emit event when signer approves admin action
+    fn _emit_action_approved_event(&self, _proposal_id: u32, _signer: AccountId) {}
+
+    /// Emit event when approved admin action is executed
+    fn _emit_action_executed_event(&self, _proposal_id: u32) {}
+}
@@ -0,0 +1,92 @@
+use crate::impls::payable_mint::types::{
+    DutchAuction,
+    SalePhase,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    storage::Mapping,
+    traits::{
+        AccountId,
+        Balance,
+        String,
+        Timestamp,
+    },
+};
+
+#[derive(Default, Debug)]
+#[openbrush::storage_item]
+pub struct Data {
+    pub signers: Vec<AccountId>,
+    pub threshold: u32,
+    pub proposal_lifetime: Timestamp,
+    pub proposal_count: u32,
+    pub proposals: Mapping<u32, Proposal>,
+    pub approvals: Mapping<(u32, AccountId), bool>,
+}
+
+/// Admin action executed once enough signers approve it
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum AdminAction {
+    /// Withdraw all funds to treasury, or to contract owner if no treasury is set
+    Withdraw,
+    /// Withdraw amount of funds to the recipient
+    WithdrawTo(AccountId, Balance),
+    /// Withdraw PSP22 token balance to treasury, or to contract owner if no treasury is set
+    WithdrawPsp22(AccountId),
+    /// Set account receiving withdrawn funds
+    SetTreasury(Option<AccountId>),
+    /// Set new value for the baseUri
+    SetBaseUri(String),
+    /// Reveal the collection by setting the real baseUri
+    Reveal(String),
+    /// Set or remove URI override of the token
+    SetTokenUri(u64, Option<String>),
+    /// Set price per token
+    SetPrice(Balance),
+    /// Permanently lock price per token
+    LockPrice,
+    /// Lower max supply of tokens
+    SetMaxSupply(u64),
+    /// Permanently close the collection at the number of minted tokens
+    CloseCollection,
+    /// Set max number of tokens which could be minted per call
+    SetMaxMintAmount(u64),
+    /// Add a payee with its share weight
+    AddPayee(AccountId, u128),
+    /// Replace signers and number of approvals required
+    SetSigners(Vec<AccountId>, u32),
+    /// Set share of the mint price credited to referrers, in basis points
+    SetReferralBps(u16),
+    /// Add a new sale phase after the existing ones
+    AddSalePhase(SalePhase),
+    /// Replace an existing sale phase
+    UpdateSalePhase(u32, SalePhase),
+    /// Set Dutch auction pricing. `None` returns to fixed price
+    SetDutchAuction(Option<DutchAuction>),
+    /// Set PSP22 token accepted as mint payment and its price per token
+    SetPsp22Payment(Option<AccountId>, Balance),
+    /// Set public key signing mint vouchers
+    SetVoucherSigner(Option<[u8; 33]>),
+    /// Store name, description and image of the token on-chain
+    #[cfg(feature = "onchain_metadata")]
+    SetTokenMetadata(u64, String, String, String),
+    /// Store trait attributes of the token on-chain
+    #[cfg(feature = "onchain_metadata")]
+    SetTokenAttributes(u64, Vec<(String, String)>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Proposal {
+    pub action: AdminAction,
+    pub proposer: AccountId,
+    pub expires_at: Timestamp,
+    pub executed: bool,
+}
@@ -0,0 +1 @@
+pub mod onchain_metadata;
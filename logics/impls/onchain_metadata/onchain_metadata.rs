@@ -0,0 +1,207 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use ink::prelude::{
+    format,
+    string::String,
+    vec::Vec,
+};
+
+use crate::impls::{
+    multisig,
+    payable_mint,
+    payable_mint::types::METADATA_MANAGER,
+};
+use openbrush::{
+    contracts::{
+        access_control,
+        access_control::only_role,
+        psp34::{
+            extensions::{
+                metadata,
+                metadata::{
+                    Id,
+                    PSP34MetadataImpl,
+                },
+            },
+            PSP34Error,
+        },
+    },
+    modifiers,
+    traits::Storage,
+};
+
+#[openbrush::trait_definition]
+pub trait OnchainMetadataImpl:
+    Storage<access_control::Data>
+    + PSP34MetadataImpl
+    + metadata::Internal
+    + payable_mint::payable_mint::Internal
+    + multisig::multisig::Internal
+{
+    /// Store name, description and image of the token on-chain.
+    /// `image` could be an URI or inline image data, e.g. `data:image/svg+xml;base64,...`
+    #[ink(message)]
+    #[modifiers(only_role(METADATA_MANAGER))]
+    fn set_token_metadata(
+        &mut self,
+        token_id: u64,
+        name: String,
+        description: String,
+        image: String,
+    ) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        self.update_token_metadata(token_id, name, description, image)
+    }
+
+    /// Store trait attributes of the token on-chain as `(trait_type, value)` pairs
+    #[ink(message)]
+    #[modifiers(only_role(METADATA_MANAGER))]
+    fn set_token_attributes(
+        &mut self,
+        token_id: u64,
+        attributes: Vec<(String, String)>,
+    ) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        self.update_token_attributes(token_id, attributes)
+    }
+
+    /// Get token URI with JSON metadata rendered from on-chain attributes
+    #[ink(message)]
+    fn onchain_token_uri(&self, token_id: u64) -> Result<String, PSP34Error> {
+        let id = Id::U64(token_id);
+        self.token_exists(id.clone())?;
+        let attribute =
+            |key: &str| PSP34MetadataImpl::get_attribute(self, id.clone(), String::from(key));
+
+        let mut json = String::from("{\"name\":");
+        push_json_string(&mut json, &attribute("name").unwrap_or_default());
+        json.push_str(",\"description\":");
+        push_json_string(&mut json, &attribute("description").unwrap_or_default());
+        json.push_str(",\"image\":");
+        push_json_string(&mut json, &attribute("image").unwrap_or_default());
+        json.push_str(",\"attributes\":");
+        json.push_str(&attribute("attributes").unwrap_or_else(|| String::from("[]")));
+        json.push('}');
+
+        Ok(String::from("data:application/json;base64,") + &base64_encode(json.as_bytes()))
+    }
+}
+
+/// Helper trait for OnchainMetadata, shared with approved multisig actions
+pub trait Internal: payable_mint::payable_mint::Internal {
+    /// Store name, description and image of the token on-chain unless metadata is frozen
+    fn update_token_metadata(
+        &mut self,
+        token_id: u64,
+        name: String,
+        description: String,
+        image: String,
+    ) -> Result<(), PSP34Error> {
+        self.check_metadata_not_frozen()?;
+        let id = Id::U64(token_id);
+        metadata::Internal::_set_attribute(self, id.clone(), String::from("name"), name);
+        metadata::Internal::_set_attribute(
+            self,
+            id.clone(),
+            String::from("description"),
+            description,
+        );
+        metadata::Internal::_set_attribute(self, id, String::from("image"), image);
+        self._emit_metadata_update_event(token_id);
+
+        Ok(())
+    }
+
+    /// Store trait attributes of the token on-chain unless metadata is frozen
+    fn update_token_attributes(
+        &mut self,
+        token_id: u64,
+        attributes: Vec<(String, String)>,
+    ) -> Result<(), PSP34Error> {
+        self.check_metadata_not_frozen()?;
+        let mut json = String::from("[");
+        for (i, (trait_type, value)) in attributes.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            json.push_str("{\"trait_type\":");
+            push_json_string(&mut json, trait_type);
+            json.push_str(",\"value\":");
+            push_json_string(&mut json, value);
+            json.push('}');
+        }
+        json.push(']');
+        metadata::Internal::_set_attribute(
+            self,
+            Id::U64(token_id),
+            String::from("attributes"),
+            json,
+        );
+        self._emit_metadata_update_event(token_id);
+
+        Ok(())
+    }
+}
+
+impl<T: payable_mint::payable_mint::Internal> Internal for T {}
+
+/// Append `value` to `json` as a quoted and escaped JSON string
+fn push_json_string(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode bytes with the standard padded base64 alphabet
+pub fn base64_encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or_default(),
+            chunk.get(2).copied().unwrap_or_default(),
+        ];
+        let triple = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (triple >> (18 - 6 * i)) & 0x3f;
+                encoded.push(BASE64_ALPHABET[index as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
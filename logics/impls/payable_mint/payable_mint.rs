@@ -19,14 +19,36 @@
 // OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
-use ink::prelude::string::{
-    String,
-    ToString,
+use ink::{
+    env::hash::Keccak256,
+    prelude::{
+        string::{
+            String,
+            ToString,
+        },
+        vec::Vec,
+    },
 };
 
-use crate::impls::payable_mint::types::{
-    Data,
-    Shiden34Error,
+use crate::impls::{
+    multisig,
+    payable_mint::types::{
+        Data,
+        DutchAuction,
+        IdAssignment,
+        SalePhase,
+        Shiden34Error,
+        TimelockOperation,
+        TimelockedOperation,
+        ADMIN,
+        FEISTEL_ROUNDS,
+        MAX_AIRDROP_AMOUNT,
+        MAX_TIMELOCK_DELAY,
+        METADATA_MANAGER,
+        MINTER,
+        TREASURER,
+    },
+    payment_splitter,
 };
 use openbrush::{
     modifiers,
@@ -34,12 +56,16 @@ use openbrush::{
         AccountId,
         Balance,
         Storage,
+        Timestamp,
     },
 };
 
 use openbrush::contracts::{
+    access_control,
+    access_control::only_role,
     ownable,
     ownable::only_owner,
+    psp22::PSP22Ref,
     psp34,
     psp34::{
         extensions::{
@@ -62,148 +88,1851 @@ pub trait PayableMintImpl:
     + Storage<psp34::Data>
     + Storage<reentrancy_guard::Data>
     + Storage<ownable::Data>
+    + Storage<access_control::Data>
     + Storage<metadata::Data>
+    + Storage<payment_splitter::types::Data>
     + PSP34Impl
     + PSP34MetadataImpl
     + psp34::extensions::metadata::Internal
+    + access_control::Internal
     + Internal
+    + multisig::multisig::Internal
+    + payment_splitter::payment_splitter::Internal
 {
     /// Mint one or more tokens
     #[ink(message, payable)]
     #[modifiers(non_reentrant)]
     fn mint(&mut self, to: AccountId, mint_amount: u64) -> Result<(), PSP34Error> {
+        self.check_not_paused()?;
+        self.check_public_mint()?;
         self.check_amount(mint_amount)?;
-        self.check_value(Self::env().transferred_value(), mint_amount)?;
+        self.collect_payment(mint_amount)?;
+        self.record_auction_mint(Self::env().caller(), mint_amount)?;
+        self.use_sale_phase(Self::env().caller(), mint_amount)?;
+        self.use_wallet_limit(Self::env().caller(), to, mint_amount)?;
+        self.mint_tokens(to, mint_amount)
+    }
+
+    /// Mint next available token for the caller
+    #[ink(message, payable)]
+    fn mint_next(&mut self) -> Result<(), PSP34Error> {
+        self.check_not_paused()?;
+        self.check_public_mint()?;
+        self.check_amount(1)?;
+        self.collect_payment(1)?;
+        let caller = Self::env().caller();
+        self.record_auction_mint(caller, 1)?;
+        self.use_sale_phase(caller, 1)?;
+        self.use_wallet_limit(caller, caller, 1)?;
+        self.mint_tokens(caller, 1)
+    }
 
-        let next_to_mint = self.data::<Data>().last_token_id + 1; // first mint id is 1
-        let mint_offset = next_to_mint + mint_amount;
+    /// Mint one or more tokens and credit the referrer with a share of the native mint price
+    #[ink(message, payable)]
+    fn mint_with_referrer(
+        &mut self,
+        to: AccountId,
+        mint_amount: u64,
+        referrer: AccountId,
+    ) -> Result<(), PSP34Error> {
+        if referrer == Self::env().caller() || referrer == to {
+            return Err(PSP34Error::Custom(Shiden34Error::SelfReferral.as_str()))
+        }
+        let paid = if Self::env().transferred_value() > 0 {
+            (mint_amount as Balance).saturating_mul(self.mint_price()?)
+        } else {
+            0
+        };
+        self.mint(to, mint_amount)?;
 
-        for mint_id in next_to_mint..mint_offset {
-            self._mint_to(to, Id::U64(mint_id))?;
-            self.data::<Data>().last_token_id += 1;
+        let reward = paid.saturating_mul(self.data::<Data>().referral_bps as Balance) / 10_000;
+        if reward > 0 {
+            let rewards = self
+                .data::<Data>()
+                .referral_rewards
+                .get(&referrer)
+                .unwrap_or_default();
+            self.data::<Data>()
+                .referral_rewards
+                .insert(&referrer, &(rewards + reward));
+            self.data::<Data>().referral_rewards_total += reward;
         }
 
         Ok(())
     }
 
-    /// Mint next available token for the caller
+    /// Mint one or more tokens for the caller from its allowlist allocation
     #[ink(message, payable)]
-    fn mint_next(&mut self) -> Result<(), PSP34Error> {
-        self.check_value(Self::env().transferred_value(), 1)?;
+    #[modifiers(non_reentrant)]
+    fn allowlist_mint(
+        &mut self,
+        mint_amount: u64,
+        allocation: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<(), PSP34Error> {
+        let caller = Self::env().caller();
+        self.check_not_paused()?;
+        self.check_amount(mint_amount)?;
+        self.collect_payment(mint_amount)?;
+        self.check_allowlist_proof(caller, allocation, &proof)?;
+
+        let minted = self
+            .data::<Data>()
+            .allowlist_minted
+            .get(&caller)
+            .unwrap_or_default();
+        let total_minted = minted
+            .checked_add(mint_amount)
+            .filter(|total| *total <= allocation)
+            .ok_or(PSP34Error::Custom(
+                Shiden34Error::AllocationExhausted.as_str(),
+            ))?;
+        self.record_auction_mint(caller, mint_amount)?;
+        self.use_sale_phase(caller, mint_amount)?;
+        self.use_wallet_limit(caller, caller, mint_amount)?;
+        self.data::<Data>()
+            .allowlist_minted
+            .insert(&caller, &total_minted);
+
+        self.mint_tokens(caller, mint_amount)
+    }
+
+    /// Mint tokens with a voucher signed off-chain by the voucher signer
+    #[ink(message, payable)]
+    #[modifiers(non_reentrant)]
+    fn mint_with_voucher(
+        &mut self,
+        to: AccountId,
+        mint_amount: u64,
+        price_per_mint: Balance,
+        nonce: u64,
+        expiry: Timestamp,
+        signature: [u8; 65],
+    ) -> Result<(), PSP34Error> {
+        self.check_not_paused()?;
+        self.check_amount(mint_amount)?;
+        if Self::env().block_timestamp() > expiry {
+            return Err(PSP34Error::Custom(Shiden34Error::VoucherExpired.as_str()))
+        }
+        if self
+            .data::<Data>()
+            .used_voucher_nonces
+            .get(&nonce)
+            .unwrap_or_default()
+        {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::VoucherAlreadyUsed.as_str(),
+            ))
+        }
+        let message_hash = self.voucher_hash(to, mint_amount, price_per_mint, nonce, expiry);
+        self.check_voucher_signature(&message_hash, &signature)?;
+        if (mint_amount as Balance).checked_mul(price_per_mint)
+            != Some(Self::env().transferred_value())
+        {
+            return Err(PSP34Error::Custom(Shiden34Error::BadMintValue.as_str()))
+        }
+        self.use_wallet_limit(Self::env().caller(), to, mint_amount)?;
+
+        self.data::<Data>()
+            .used_voucher_nonces
+            .insert(&nonce, &true);
+        self.mint_tokens(to, mint_amount)
+    }
+
+    /// Transfer referral rewards of the caller
+    #[ink(message)]
+    #[modifiers(non_reentrant)]
+    fn claim_referral_rewards(&mut self) -> Result<(), PSP34Error> {
         let caller = Self::env().caller();
-        let token_id = self
+        let rewards = self
             .data::<Data>()
+            .referral_rewards
+            .get(&caller)
+            .unwrap_or_default();
+        if rewards == 0 {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::NoReferralRewards.as_str(),
+            ))
+        }
+        self.data::<Data>().referral_rewards.remove(&caller);
+        self.data::<Data>().referral_rewards_total -= rewards;
+
+        Self::env()
+            .transfer(caller, rewards)
+            .map_err(|_| PSP34Error::Custom(Shiden34Error::WithdrawalFailed.as_str()))?;
+        Ok(())
+    }
+
+    /// Set share of the mint price credited to referrers, in basis points
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn set_referral_bps(&mut self, referral_bps: u16) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        self.update_referral_bps(referral_bps)
+    }
+
+    /// Mint tokens free of charge to each recipient
+    #[ink(message)]
+    #[modifiers(only_role(MINTER))]
+    fn airdrop(&mut self, recipients: Vec<(AccountId, u64)>) -> Result<(), PSP34Error> {
+        let total_amount = recipients
+            .iter()
+            .try_fold(0u64, |total, (_, amount)| total.checked_add(*amount))
+            .filter(|total| *total <= MAX_AIRDROP_AMOUNT)
+            .ok_or(PSP34Error::Custom(Shiden34Error::AirdropTooLarge.as_str()))?;
+        if total_amount == 0 {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::CannotMintZeroTokens.as_str(),
+            ))
+        }
+        self.data::<Data>()
             .last_token_id
-            .checked_add(1)
+            .checked_add(total_amount)
+            .filter(|amount| *amount <= self.data::<Data>().max_supply)
             .ok_or(PSP34Error::Custom(Shiden34Error::CollectionIsFull.as_str()))?;
-        self._mint_to(caller, Id::U64(token_id))?;
-        self.data::<Data>().last_token_id += 1;
+
+        for (to, amount) in recipients {
+            self.mint_tokens(to, amount)?;
+        }
 
         Ok(())
     }
 
-    /// Set new value for the baseUri
+    /// Set Merkle root of the allowlist. While set, only allowlist minting is open
     #[ink(message)]
-    #[modifiers(only_owner)]
-    fn set_base_uri(&mut self, uri: String) -> Result<(), PSP34Error> {
-        let id = PSP34Impl::collection_id(self);
-        metadata::Internal::_set_attribute(self, id, String::from("baseUri"), uri);
+    #[modifiers(only_role(ADMIN))]
+    fn set_allowlist_root(&mut self, root: Option<[u8; 32]>) -> Result<(), PSP34Error> {
+        self.data::<Data>().allowlist_root = root;
 
         Ok(())
     }
 
-    /// Withdraws funds to contract owner
+    /// Add a new sale phase after the existing ones
     #[ink(message)]
-    #[modifiers(only_owner)]
-    fn withdraw(&mut self) -> Result<(), PSP34Error> {
-        let balance = Self::env().balance();
-        let current_balance = balance
-            .checked_sub(Self::env().minimum_balance())
+    #[modifiers(only_role(ADMIN))]
+    fn add_sale_phase(&mut self, phase: SalePhase) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        self.append_sale_phase(phase)
+    }
+
+    /// Replace an existing sale phase. Tokens already minted in it are kept
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn update_sale_phase(&mut self, phase_id: u32, phase: SalePhase) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        self.replace_sale_phase(phase_id, phase)
+    }
+
+    /// Set max number of tokens an account could mint over its lifetime. `None` removes the limit
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn set_max_per_wallet(&mut self, max_per_wallet: Option<u64>) -> Result<(), PSP34Error> {
+        self.data::<Data>().max_per_wallet = max_per_wallet;
+
+        Ok(())
+    }
+
+    /// Set whether tokens minted to another account also count against the recipient's limit
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn set_wallet_limit_counts_recipient(
+        &mut self,
+        counts_recipient: bool,
+    ) -> Result<(), PSP34Error> {
+        self.data::<Data>().wallet_limit_counts_recipient = counts_recipient;
+
+        Ok(())
+    }
+
+    /// Set Dutch auction pricing. `None` returns to fixed price. Locked after the first auction mint
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn set_dutch_auction(&mut self, auction: Option<DutchAuction>) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        self.update_dutch_auction(auction)
+    }
+
+    /// Claim the difference between the price paid in the Dutch auction and its clearing price
+    #[ink(message)]
+    #[modifiers(non_reentrant)]
+    fn claim_auction_rebate(&mut self) -> Result<(), PSP34Error> {
+        let auction = self
+            .data::<Data>()
+            .dutch_auction
+            .clone()
+            .filter(|auction| auction.rebate)
+            .ok_or(PSP34Error::Custom(Shiden34Error::NoRebateToClaim.as_str()))?;
+        if !self.auction_ended(&auction) {
+            return Err(PSP34Error::Custom(Shiden34Error::AuctionNotEnded.as_str()))
+        }
+
+        let caller = Self::env().caller();
+        let rebate = self.auction_rebate(caller);
+        if rebate == 0 {
+            return Err(PSP34Error::Custom(Shiden34Error::NoRebateToClaim.as_str()))
+        }
+        let paid = self
+            .data::<Data>()
+            .auction_paid
+            .get(&caller)
             .unwrap_or_default();
-        let owner = self.data::<ownable::Data>().owner.get().unwrap().unwrap();
+        self.data::<Data>()
+            .auction_paid
+            .insert(&caller, &(paid - rebate));
+        self.data::<Data>().auction_total_paid -= rebate;
+
         Self::env()
-            .transfer(owner, current_balance)
+            .transfer(caller, rebate)
             .map_err(|_| PSP34Error::Custom(Shiden34Error::WithdrawalFailed.as_str()))?;
         Ok(())
     }
 
-    /// Set max number of tokens which could be minted per call
+    /// Reveal the seed which shuffles metadata of tokens with random ids, once the collection
+    /// is sold out or all sale phases are over. It must match the commitment given at
+    /// construction, and no tokens could be minted afterwards
     #[ink(message)]
-    #[modifiers(only_owner)]
-    fn set_max_mint_amount(&mut self, max_amount: u64) -> Result<(), PSP34Error> {
-        self.data::<Data>().max_amount = max_amount;
+    #[modifiers(only_role(ADMIN))]
+    fn reveal_random_seed(&mut self, seed: [u8; 32]) -> Result<(), PSP34Error> {
+        if !self.sale_ended() {
+            return Err(PSP34Error::Custom(Shiden34Error::SaleNotEnded.as_str()))
+        }
+        let mut seed_hash = [0u8; 32];
+        ink::env::hash_bytes::<Keccak256>(&seed, &mut seed_hash);
+        if self.data::<Data>().random_seed.is_some()
+            || self.data::<Data>().random_seed_commitment != Some(seed_hash)
+        {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::InvalidRandomSeed.as_str(),
+            ))
+        }
+        self.data::<Data>().random_seed = Some(seed);
 
         Ok(())
     }
 
-    /// Get URI from token ID
+    /// Set compressed secp256k1 public key which signs mint vouchers. `None` disables vouchers
     #[ink(message)]
-    fn token_uri(&self, token_id: u64) -> Result<String, PSP34Error> {
-        self.token_exists(Id::U64(token_id))?;
+    #[modifiers(only_role(ADMIN))]
+    fn set_voucher_signer(&mut self, signer: Option<[u8; 33]>) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        self.data::<Data>().voucher_signer = signer;
+
+        Ok(())
+    }
+
+    /// Pause minting
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn pause(&mut self) -> Result<(), PSP34Error> {
+        self.check_not_paused()?;
+        self.data::<Data>().paused = true;
+        self._emit_paused_event(Self::env().caller());
+
+        Ok(())
+    }
+
+    /// Unpause minting
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn unpause(&mut self) -> Result<(), PSP34Error> {
+        if !self.data::<Data>().paused {
+            return Err(PSP34Error::Custom(Shiden34Error::MintingNotPaused.as_str()))
+        }
+        self.data::<Data>().paused = false;
+        self._emit_unpaused_event(Self::env().caller());
+
+        Ok(())
+    }
+
+    /// Set new value for the baseUri. Queued while the timelock delay is set
+    #[ink(message)]
+    #[modifiers(only_role(METADATA_MANAGER))]
+    fn set_base_uri(&mut self, uri: String) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        self.check_metadata_not_frozen()?;
+        if self.data::<Data>().timelock_delay > 0 {
+            return self.queue_operation(TimelockOperation::SetBaseUri(uri))
+        }
+        self.update_base_uri(uri)
+    }
+
+    /// Set URI returned for the token instead of the one built from baseUri.
+    /// `None` removes the override
+    #[ink(message)]
+    #[modifiers(only_role(METADATA_MANAGER))]
+    fn set_token_uri(&mut self, token_id: u64, uri: Option<String>) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        self.update_token_uri(token_id, uri)
+    }
+
+    /// Permanently lock URI of the token
+    #[ink(message)]
+    #[modifiers(only_role(METADATA_MANAGER))]
+    fn lock_token_uri(&mut self, token_id: u64) -> Result<(), PSP34Error> {
+        self.data::<Data>()
+            .locked_token_uris
+            .insert(&token_id, &true);
+
+        Ok(())
+    }
+
+    /// Permanently freeze baseUri and token metadata. The collection must be revealed first
+    /// if it has a placeholder, and have its starting index set if it has a provenance hash
+    #[ink(message)]
+    #[modifiers(only_role(METADATA_MANAGER))]
+    fn freeze_metadata(&mut self) -> Result<(), PSP34Error> {
+        self.check_metadata_not_frozen()?;
+        if self.data::<Data>().placeholder_uri.is_some() && !self.data::<Data>().revealed {
+            return Err(PSP34Error::Custom(Shiden34Error::NotRevealed.as_str()))
+        }
+        if self.data::<Data>().provenance_hash.is_some()
+            && self.data::<Data>().starting_index.is_none()
+        {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::StartingIndexNotSet.as_str(),
+            ))
+        }
+        if self.data::<Data>().id_assignment == IdAssignment::Random
+            && self.data::<Data>().random_seed.is_none()
+        {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::RandomSeedNotRevealed.as_str(),
+            ))
+        }
+        self.data::<Data>().metadata_frozen = true;
         let base_uri = PSP34MetadataImpl::get_attribute(
             self,
             PSP34Impl::collection_id(self),
             String::from("baseUri"),
         );
-        let token_uri = base_uri.unwrap() + &token_id.to_string() + &String::from(".json");
-        Ok(token_uri)
+        self._emit_permanent_uri_event(base_uri.unwrap_or_default());
+
+        Ok(())
     }
 
-    /// Get max supply of tokens
+    /// Set URI returned for every token until the collection is revealed
     #[ink(message)]
-    fn max_supply(&self) -> u64 {
-        self.data::<Data>().max_supply
+    #[modifiers(only_role(METADATA_MANAGER))]
+    fn set_placeholder_uri(&mut self, uri: String) -> Result<(), PSP34Error> {
+        self.check_metadata_not_frozen()?;
+        self.check_not_revealed()?;
+        self.data::<Data>().placeholder_uri = Some(uri);
+
+        Ok(())
     }
 
-    /// Get token price
+    /// Reveal the collection by setting the real baseUri. This can only be done once.
+    /// Queued while the timelock delay is set
     #[ink(message)]
-    fn price(&self) -> Balance {
-        self.data::<Data>().price_per_mint
+    #[modifiers(only_role(METADATA_MANAGER))]
+    fn reveal(&mut self, base_uri: String) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        if self.data::<Data>().timelock_delay > 0 {
+            self.check_metadata_not_frozen()?;
+            self.check_not_revealed()?;
+            return self.queue_operation(TimelockOperation::Reveal(base_uri))
+        }
+        self.reveal_base_uri(base_uri)
     }
 
-    /// Get max number of tokens which could be minted per call
+    /// Set provenance hash of the collection art, along with the Keccak-256 hash of the seed
+    /// which derives the starting index. Frozen once the first token is minted
     #[ink(message)]
-    fn get_max_mint_amount(&mut self) -> u64 {
-        self.data::<Data>().max_amount
+    #[modifiers(only_role(METADATA_MANAGER))]
+    fn set_provenance_hash(
+        &mut self,
+        provenance_hash: [u8; 32],
+        seed_commitment: [u8; 32],
+    ) -> Result<(), PSP34Error> {
+        if self.data::<Data>().last_token_id > 0 {
+            return Err(PSP34Error::Custom(Shiden34Error::ProvenanceFrozen.as_str()))
+        }
+        self.data::<Data>().provenance_hash = Some(provenance_hash);
+        self.data::<Data>().starting_index_commitment = Some(seed_commitment);
+
+        Ok(())
     }
-}
 
-/// Helper trait for PayableMint
-pub trait Internal: Storage<Data> + psp34::Internal {
-    /// Check if the transferred mint values is as expected
-    fn check_value(&self, transferred_value: u128, mint_amount: u64) -> Result<(), PSP34Error> {
-        if let Some(value) = (mint_amount as u128).checked_mul(self.data::<Data>().price_per_mint) {
-            if transferred_value == value {
-                return Ok(())
+    /// Derive the starting index from the seed committed with the provenance hash, once the
+    /// collection is sold out or all sale phases are over. It can be set only once, and not
+    /// after metadata is frozen
+    #[ink(message)]
+    fn set_starting_index(&mut self, seed: [u8; 32]) -> Result<(), PSP34Error> {
+        self.check_metadata_not_frozen()?;
+        if self.data::<Data>().starting_index.is_some() {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::StartingIndexAlreadySet.as_str(),
+            ))
+        }
+        if !self.sale_ended() {
+            return Err(PSP34Error::Custom(Shiden34Error::SaleNotEnded.as_str()))
+        }
+        let mut seed_hash = [0u8; 32];
+        ink::env::hash_bytes::<Keccak256>(&seed, &mut seed_hash);
+        if self.data::<Data>().starting_index_commitment != Some(seed_hash) {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::InvalidStartingIndexSeed.as_str(),
+            ))
+        }
+
+        // the seed is fixed before minting, so the caller can't pick a favourable offset
+        let mut entropy = [0u8; 32];
+        ink::env::hash_encoded::<Keccak256, _>(
+            &(seed, self.data::<Data>().provenance_hash),
+            &mut entropy,
+        );
+        let mut index_bytes = [0u8; 8];
+        index_bytes.copy_from_slice(&entropy[..8]);
+        // an offset of 0 would leave the mapping unchanged, so it's drawn from 1..max_supply.
+        // A collection of a single token has no other offset
+        let max_supply = self.data::<Data>().max_supply;
+        let starting_index = if max_supply > 1 {
+            u64::from_le_bytes(index_bytes) % (max_supply - 1) + 1
+        } else {
+            0
+        };
+        self.data::<Data>().starting_index = Some(starting_index);
+
+        Ok(())
+    }
+
+    /// Withdraws funds to treasury, or to contract owner if no treasury is set.
+    /// Disabled once payees share the funds. Queued while the timelock delay is set,
+    /// in which case the recipient is fixed when queued
+    #[ink(message)]
+    #[modifiers(only_role(TREASURER))]
+    fn withdraw(&mut self) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        if self.data::<Data>().timelock_delay > 0 {
+            let recipient = self.withdrawal_recipient()?;
+            return self.queue_operation(TimelockOperation::Withdraw(recipient))
+        }
+        self.withdraw_balance()
+    }
+
+    /// Withdraws `amount` of funds to `recipient`. Disabled once payees share the funds.
+    /// Queued while the timelock delay is set
+    #[ink(message)]
+    #[modifiers(only_role(TREASURER))]
+    fn withdraw_to(&mut self, recipient: AccountId, amount: Balance) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        if self.data::<Data>().timelock_delay > 0 {
+            return self.queue_operation(TimelockOperation::WithdrawTo(recipient, amount))
+        }
+        self.withdraw_balance_to(recipient, amount)
+    }
+
+    /// Set account receiving withdrawn funds. `None` withdraws to contract owner
+    #[ink(message)]
+    #[modifiers(only_role(TREASURER))]
+    fn set_treasury(&mut self, treasury: Option<AccountId>) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        self.data::<Data>().treasury = treasury;
+
+        Ok(())
+    }
+
+    /// Propose new contract owner. Ownership is handed over once they accept it
+    #[ink(message)]
+    #[modifiers(only_owner)]
+    fn propose_owner(&mut self, new_owner: AccountId) -> Result<(), PSP34Error> {
+        self.data::<Data>().pending_owner = Some(new_owner);
+        self._emit_ownership_proposed_event(new_owner);
+
+        Ok(())
+    }
+
+    /// Accept ownership proposed to the caller, together with the admin and operational roles
+    /// held by the previous owner
+    #[ink(message)]
+    fn accept_ownership(&mut self) -> Result<(), PSP34Error> {
+        let caller = Self::env().caller();
+        if self.data::<Data>().pending_owner != Some(caller) {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::CallerIsNotPendingOwner.as_str(),
+            ))
+        }
+        let previous_owner = self.data::<ownable::Data>().owner.get().flatten();
+        for role in [
+            access_control::DEFAULT_ADMIN_ROLE,
+            ADMIN,
+            MINTER,
+            METADATA_MANAGER,
+            TREASURER,
+        ] {
+            access_control::Internal::_setup_role(self, role, Some(caller));
+            if let Some(previous_owner) = previous_owner.filter(|owner| *owner != caller) {
+                if access_control::Internal::_check_role(self, role, Some(previous_owner)).is_ok() {
+                    access_control::Internal::_do_revoke_role(self, role, Some(previous_owner));
+                }
             }
         }
-        Err(PSP34Error::Custom(Shiden34Error::BadMintValue.as_str()))
+        self.data::<ownable::Data>().owner.set(&Some(caller));
+        self.data::<Data>().pending_owner = None;
+        self._emit_ownership_accepted_event(previous_owner, caller);
+
+        Ok(())
     }
 
-    /// Check amount of tokens to be minted
-    fn check_amount(&self, mint_amount: u64) -> Result<(), PSP34Error> {
-        if mint_amount == 0 {
+    /// Cancel pending ownership transfer
+    #[ink(message)]
+    #[modifiers(only_owner)]
+    fn cancel_ownership_transfer(&mut self) -> Result<(), PSP34Error> {
+        let pending_owner = self
+            .data::<Data>()
+            .pending_owner
+            .take()
+            .ok_or(PSP34Error::Custom(Shiden34Error::NoPendingOwner.as_str()))?;
+        self._emit_ownership_transfer_cancelled_event(pending_owner);
+
+        Ok(())
+    }
+
+    /// Set delay for which withdrawals, new payees, reveal, baseUri and max mint amount changes
+    /// are queued before they could be executed. The delay can't be shortened,
+    /// nor exceed `MAX_TIMELOCK_DELAY`
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn set_timelock_delay(&mut self, delay: Timestamp) -> Result<(), PSP34Error> {
+        if delay < self.data::<Data>().timelock_delay || delay > MAX_TIMELOCK_DELAY {
             return Err(PSP34Error::Custom(
-                Shiden34Error::CannotMintZeroTokens.as_str(),
+                Shiden34Error::InvalidTimelockDelay.as_str(),
             ))
         }
-        if mint_amount > self.data::<Data>().max_amount {
+        self.data::<Data>().timelock_delay = delay;
+
+        Ok(())
+    }
+
+    /// Execute queued operation once its delay has passed
+    #[ink(message)]
+    #[modifiers(non_reentrant)]
+    fn execute_operation(&mut self, operation_id: u32) -> Result<(), PSP34Error> {
+        let eta = self
+            .data::<Data>()
+            .timelocked_operations
+            .get(&operation_id)
+            .ok_or(PSP34Error::Custom(Shiden34Error::UnknownOperation.as_str()))?
+            .eta;
+        if Self::env().block_timestamp() < eta {
             return Err(PSP34Error::Custom(
-                Shiden34Error::TooManyTokensToMint.as_str(),
+                Shiden34Error::TimelockNotExpired.as_str(),
             ))
         }
-        if let Some(amount) = self.data::<Data>().last_token_id.checked_add(mint_amount) {
-            if amount <= self.data::<Data>().max_supply {
-                return Ok(())
+        let timelocked = self.take_timelocked_operation(operation_id)?;
+        match timelocked.operation {
+            TimelockOperation::Withdraw(recipient) => {
+                let amount = self.withdrawable_balance()?;
+                self.transfer_withdrawal(recipient, amount)?
+            }
+            TimelockOperation::WithdrawTo(recipient, amount) => {
+                self.withdraw_balance_to(recipient, amount)?
+            }
+            TimelockOperation::SetBaseUri(uri) => self.update_base_uri(uri)?,
+            TimelockOperation::Reveal(base_uri) => self.reveal_base_uri(base_uri)?,
+            TimelockOperation::SetMaxMintAmount(max_amount) => {
+                self.data::<Data>().max_amount = max_amount
             }
+            TimelockOperation::WithdrawPsp22(token, recipient) => {
+                self.withdraw_psp22_balance_to(token, recipient)?
+            }
+            TimelockOperation::AddPayee(account, shares) => self._add_payee(account, shares)?,
         }
+        self._emit_operation_executed_event(operation_id);
 
-        Err(PSP34Error::Custom(Shiden34Error::CollectionIsFull.as_str()))
+        Ok(())
     }
 
-    /// Check if token is minted
-    fn token_exists(&self, id: Id) -> Result<(), PSP34Error> {
-        self._owner_of(&id).ok_or(PSP34Error::TokenNotExists)?;
+    /// Cancel queued operation
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn cancel_operation(&mut self, operation_id: u32) -> Result<(), PSP34Error> {
+        self.take_timelocked_operation(operation_id)?;
+        self._emit_operation_cancelled_event(operation_id);
+
+        Ok(())
+    }
+
+    /// Set whether value transferred above the mint price is accepted and refunded,
+    /// instead of failing with `BadMintValue`
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn set_refund_excess(&mut self, refund_excess: bool) -> Result<(), PSP34Error> {
+        self.data::<Data>().refund_excess = refund_excess;
+
+        Ok(())
+    }
+
+    /// Set PSP22 token accepted as mint payment and its price per token. `None` disables it
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn set_psp22_payment(
+        &mut self,
+        token: Option<AccountId>,
+        price_per_mint: Balance,
+    ) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        self.data::<Data>().psp22_token = token;
+        self.data::<Data>().psp22_price_per_mint = price_per_mint;
+
         Ok(())
     }
+
+    /// Withdraws PSP22 token balance to treasury, or to contract owner if no treasury is set.
+    /// Disabled once payees share the funds, they are paid with `release_psp22` instead.
+    /// Queued while the timelock delay is set, in which case the recipient is fixed when queued
+    #[ink(message)]
+    #[modifiers(only_role(TREASURER), non_reentrant)]
+    fn withdraw_psp22(&mut self, token: AccountId) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        if self.data::<Data>().timelock_delay > 0 {
+            let recipient = self.withdrawal_recipient()?;
+            return self.queue_operation(TimelockOperation::WithdrawPsp22(token, recipient))
+        }
+        self.withdraw_psp22_balance(token)
+    }
+
+    /// Set price per token
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn set_price(&mut self, price_per_mint: Balance) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        self.update_price(price_per_mint)
+    }
+
+    /// Permanently lock price per token
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn lock_price(&mut self) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        self.data::<Data>().price_locked = true;
+
+        Ok(())
+    }
+
+    /// Lower max supply of tokens. It can't go below the number of minted tokens,
+    /// nor be changed when tokens get random ids
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn set_max_supply(&mut self, max_supply: u64) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        self.update_max_supply(max_supply)
+    }
+
+    /// Permanently close the collection at the number of minted tokens. Not available
+    /// when tokens get random ids
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn close_collection(&mut self) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        self.close_supply()
+    }
+
+    /// Set max number of tokens which could be minted per call.
+    /// Queued while the timelock delay is set
+    #[ink(message)]
+    #[modifiers(only_role(ADMIN))]
+    fn set_max_mint_amount(&mut self, max_amount: u64) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        if self.data::<Data>().timelock_delay > 0 {
+            return self.queue_operation(TimelockOperation::SetMaxMintAmount(max_amount))
+        }
+        self.data::<Data>().max_amount = max_amount;
+
+        Ok(())
+    }
+
+    /// Get URI from token ID
+    #[ink(message)]
+    fn token_uri(&self, token_id: u64) -> Result<String, PSP34Error> {
+        self.token_exists(Id::U64(token_id))?;
+        if !self.data::<Data>().revealed {
+            if let Some(placeholder_uri) = &self.data::<Data>().placeholder_uri {
+                return Ok(placeholder_uri.clone())
+            }
+        }
+        if let Some(token_uri) = self.data::<Data>().token_uris.get(&token_id) {
+            return Ok(token_uri)
+        }
+        let base_uri = PSP34MetadataImpl::get_attribute(
+            self,
+            PSP34Impl::collection_id(self),
+            String::from("baseUri"),
+        );
+        // metadata files are numbered 1..=max_supply like token ids
+        let max_supply = self.data::<Data>().max_supply;
+        let mut metadata_id = token_id;
+        if self.data::<Data>().id_assignment == IdAssignment::Random {
+            let seed = self.data::<Data>().random_seed.ok_or(PSP34Error::Custom(
+                Shiden34Error::RandomSeedNotRevealed.as_str(),
+            ))?;
+            metadata_id = shuffled_id(&seed, token_id, max_supply);
+        }
+        if let Some(starting_index) = self.data::<Data>().starting_index {
+            metadata_id = (metadata_id - 1 + starting_index) % max_supply + 1;
+        }
+        let token_uri = base_uri.unwrap() + &metadata_id.to_string() + &String::from(".json");
+        Ok(token_uri)
+    }
+
+    /// Get URI returned for every token until the collection is revealed
+    #[ink(message)]
+    fn placeholder_uri(&self) -> Option<String> {
+        self.data::<Data>().placeholder_uri.clone()
+    }
+
+    /// Check if the collection is revealed
+    #[ink(message)]
+    fn is_revealed(&self) -> bool {
+        self.data::<Data>().revealed
+    }
+
+    /// Check if URI of the token is permanently locked
+    #[ink(message)]
+    fn is_token_uri_locked(&self, token_id: u64) -> bool {
+        self.data::<Data>()
+            .locked_token_uris
+            .get(&token_id)
+            .unwrap_or(false)
+    }
+
+    /// Check if metadata is permanently frozen
+    #[ink(message)]
+    fn is_metadata_frozen(&self) -> bool {
+        self.data::<Data>().metadata_frozen
+    }
+
+    /// Get provenance hash of the collection art
+    #[ink(message)]
+    fn provenance_hash(&self) -> Option<[u8; 32]> {
+        self.data::<Data>().provenance_hash
+    }
+
+    /// Get offset applied to token ids in token URIs
+    #[ink(message)]
+    fn starting_index(&self) -> Option<u64> {
+        self.data::<Data>().starting_index
+    }
+
+    /// Get max supply of tokens
+    #[ink(message)]
+    fn max_supply(&self) -> u64 {
+        self.data::<Data>().max_supply
+    }
+
+    /// Check if the collection is permanently closed
+    #[ink(message)]
+    fn is_collection_closed(&self) -> bool {
+        self.data::<Data>().collection_closed
+    }
+
+    /// Get token price
+    #[ink(message)]
+    fn price(&self) -> Balance {
+        self.mint_price()
+            .unwrap_or(self.data::<Data>().price_per_mint)
+    }
+
+    /// Check if price per token is locked
+    #[ink(message)]
+    fn is_price_locked(&self) -> bool {
+        self.data::<Data>().price_locked
+    }
+
+    /// Get max number of tokens which could be minted per call
+    #[ink(message)]
+    fn get_max_mint_amount(&mut self) -> u64 {
+        self.data::<Data>().max_amount
+    }
+
+    /// Get Merkle root of the allowlist
+    #[ink(message)]
+    fn allowlist_root(&self) -> Option<[u8; 32]> {
+        self.data::<Data>().allowlist_root
+    }
+
+    /// Get number of tokens minted by the account from its allowlist allocation
+    #[ink(message)]
+    fn allowlist_minted(&self, account: AccountId) -> u64 {
+        self.data::<Data>()
+            .allowlist_minted
+            .get(&account)
+            .unwrap_or_default()
+    }
+
+    /// Check if minting is paused
+    #[ink(message)]
+    fn paused(&self) -> bool {
+        self.data::<Data>().paused
+    }
+
+    /// Get compressed secp256k1 public key which signs mint vouchers
+    #[ink(message)]
+    fn voucher_signer(&self) -> Option<[u8; 33]> {
+        self.data::<Data>().voucher_signer
+    }
+
+    /// Check if the voucher nonce is already used
+    #[ink(message)]
+    fn is_voucher_used(&self, nonce: u64) -> bool {
+        self.data::<Data>()
+            .used_voucher_nonces
+            .get(&nonce)
+            .unwrap_or_default()
+    }
+
+    /// Get how ids are assigned to newly minted tokens
+    #[ink(message)]
+    fn id_assignment(&self) -> IdAssignment {
+        self.data::<Data>().id_assignment
+    }
+
+    /// Get revealed seed for random token ids
+    #[ink(message)]
+    fn random_seed(&self) -> Option<[u8; 32]> {
+        self.data::<Data>().random_seed
+    }
+
+    /// Get max number of tokens an account could mint over its lifetime
+    #[ink(message)]
+    fn max_per_wallet(&self) -> Option<u64> {
+        self.data::<Data>().max_per_wallet
+    }
+
+    /// Get number of tokens counted against the account's lifetime limit
+    #[ink(message)]
+    fn wallet_minted(&self, account: AccountId) -> u64 {
+        self.data::<Data>()
+            .wallet_minted
+            .get(&account)
+            .unwrap_or_default()
+    }
+
+    /// Get number of tokens the account could still mint. `None` if there is no limit
+    #[ink(message)]
+    fn wallet_mints_remaining(&self, account: AccountId) -> Option<u64> {
+        let minted = self
+            .data::<Data>()
+            .wallet_minted
+            .get(&account)
+            .unwrap_or_default();
+        self.data::<Data>()
+            .max_per_wallet
+            .map(|max| max.saturating_sub(minted))
+    }
+
+    /// Check if value transferred above the mint price is refunded
+    #[ink(message)]
+    fn refund_excess(&self) -> bool {
+        self.data::<Data>().refund_excess
+    }
+
+    /// Get account receiving withdrawn funds
+    #[ink(message)]
+    fn treasury(&self) -> Option<AccountId> {
+        self.data::<Data>().treasury
+    }
+
+    /// Get account proposed as new contract owner
+    #[ink(message)]
+    fn pending_owner(&self) -> Option<AccountId> {
+        self.data::<Data>().pending_owner
+    }
+
+    /// Get delay for which operations are queued
+    #[ink(message)]
+    fn timelock_delay(&self) -> Timestamp {
+        self.data::<Data>().timelock_delay
+    }
+
+    /// Get queued operations with their ids
+    #[ink(message)]
+    fn pending_operations(&self) -> Vec<(u32, TimelockedOperation)> {
+        self.data::<Data>()
+            .pending_operation_ids
+            .iter()
+            .filter_map(|id| {
+                self.data::<Data>()
+                    .timelocked_operations
+                    .get(id)
+                    .map(|timelocked| (*id, timelocked))
+            })
+            .collect()
+    }
+
+    /// Get share of the mint price credited to referrers, in basis points
+    #[ink(message)]
+    fn referral_bps(&self) -> u16 {
+        self.data::<Data>().referral_bps
+    }
+
+    /// Get referral rewards the account could claim
+    #[ink(message)]
+    fn referral_rewards(&self, account: AccountId) -> Balance {
+        self.data::<Data>()
+            .referral_rewards
+            .get(&account)
+            .unwrap_or_default()
+    }
+
+    /// Get PSP22 token accepted as mint payment with its price per token
+    #[ink(message)]
+    fn psp22_payment(&self) -> Option<(AccountId, Balance)> {
+        self.data::<Data>()
+            .psp22_token
+            .map(|token| (token, self.data::<Data>().psp22_price_per_mint))
+    }
+
+    /// Get Dutch auction pricing, if enabled
+    #[ink(message)]
+    fn dutch_auction(&self) -> Option<DutchAuction> {
+        self.data::<Data>().dutch_auction.clone()
+    }
+
+    /// Get rebate the account could claim at the current clearing price
+    #[ink(message)]
+    fn auction_rebate(&self, account: AccountId) -> Balance {
+        let paid = self
+            .data::<Data>()
+            .auction_paid
+            .get(&account)
+            .unwrap_or_default();
+        let minted = self
+            .data::<Data>()
+            .auction_minted
+            .get(&account)
+            .unwrap_or_default();
+        paid.saturating_sub(
+            self.data::<Data>()
+                .auction_clearing_price
+                .saturating_mul(minted as Balance),
+        )
+    }
+
+    /// Get sale phase by id
+    #[ink(message)]
+    fn sale_phase(&self, phase_id: u32) -> Option<SalePhase> {
+        self.data::<Data>().sale_phases.get(&phase_id)
+    }
+
+    /// Get the active sale phase with its id and remaining supply
+    #[ink(message)]
+    fn current_sale_phase(&self) -> Option<(u32, SalePhase, u64)> {
+        let (phase_id, phase) = self.active_sale_phase()?;
+        let phase_minted = self
+            .data::<Data>()
+            .sale_phase_minted
+            .get(&phase_id)
+            .unwrap_or_default();
+        let collection_remaining = self
+            .data::<Data>()
+            .max_supply
+            .saturating_sub(self.data::<Data>().last_token_id);
+        let remaining = phase
+            .max_supply
+            .saturating_sub(phase_minted)
+            .min(collection_remaining);
+
+        Some((phase_id, phase, remaining))
+    }
+}
+
+/// Helper trait for PayableMint
+pub trait Internal:
+    Storage<Data>
+    + Storage<ownable::Data>
+    + Storage<payment_splitter::types::Data>
+    + psp34::Internal
+    + PSP34Impl
+    + psp34::extensions::metadata::Internal
+{
+    /// Check if the transferred mint values is as expected
+    fn check_value(&self, transferred_value: u128, mint_amount: u64) -> Result<(), PSP34Error> {
+        if let Some(value) = (mint_amount as u128).checked_mul(self.mint_price()?) {
+            if transferred_value == value
+                || (self.data::<Data>().refund_excess && transferred_value > value)
+            {
+                return Ok(())
+            }
+        }
+        Err(PSP34Error::Custom(Shiden34Error::BadMintValue.as_str()))
+    }
+
+    /// Take payment for the tokens. Without transferred value the PSP22 price is pulled
+    /// from the caller via `transfer_from`, as long as no Dutch auction sets the price.
+    /// Sale phases are priced in native tokens only, so PSP22 payment is refused once
+    /// any phase is configured
+    fn collect_payment(&mut self, mint_amount: u64) -> Result<(), PSP34Error> {
+        let transferred_value = Self::env().transferred_value();
+        let token = match self.data::<Data>().psp22_token {
+            Some(token)
+                if transferred_value == 0 && self.data::<Data>().dutch_auction.is_none() =>
+            {
+                token
+            }
+            _ => {
+                self.check_value(transferred_value, mint_amount)?;
+                return self.refund_overpayment(transferred_value, mint_amount)
+            }
+        };
+        if self.data::<Data>().sale_phase_count > 0 {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::Psp22PaymentUnavailable.as_str(),
+            ))
+        }
+        let value = (mint_amount as Balance)
+            .checked_mul(self.data::<Data>().psp22_price_per_mint)
+            .ok_or(PSP34Error::Custom(Shiden34Error::BadMintValue.as_str()))?;
+        PSP22Ref::transfer_from(
+            &token,
+            Self::env().caller(),
+            Self::env().account_id(),
+            value,
+            Vec::new(),
+        )
+        .map_err(|_| PSP34Error::Custom(Shiden34Error::Psp22PaymentFailed.as_str()))
+    }
+
+    /// Send value transferred above the mint price back to the caller
+    fn refund_overpayment(
+        &mut self,
+        transferred_value: Balance,
+        mint_amount: u64,
+    ) -> Result<(), PSP34Error> {
+        let excess = transferred_value
+            .saturating_sub((mint_amount as Balance).saturating_mul(self.mint_price()?));
+        if excess == 0 {
+            return Ok(())
+        }
+        let caller = Self::env().caller();
+        Self::env()
+            .transfer(caller, excess)
+            .map_err(|_| PSP34Error::Custom(Shiden34Error::RefundFailed.as_str()))?;
+        self._emit_refund_event(caller, excess);
+        Ok(())
+    }
+
+    /// Check amount of tokens to be minted
+    fn check_amount(&self, mint_amount: u64) -> Result<(), PSP34Error> {
+        if mint_amount == 0 {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::CannotMintZeroTokens.as_str(),
+            ))
+        }
+        if mint_amount > self.data::<Data>().max_amount {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::TooManyTokensToMint.as_str(),
+            ))
+        }
+        if let Some(amount) = self.data::<Data>().last_token_id.checked_add(mint_amount) {
+            if amount <= self.data::<Data>().max_supply {
+                return Ok(())
+            }
+        }
+
+        Err(PSP34Error::Custom(Shiden34Error::CollectionIsFull.as_str()))
+    }
+
+    /// Check if token is minted
+    fn token_exists(&self, id: Id) -> Result<(), PSP34Error> {
+        self._owner_of(&id).ok_or(PSP34Error::TokenNotExists)?;
+        Ok(())
+    }
+
+    /// Check if minting is not paused
+    fn check_not_paused(&self) -> Result<(), PSP34Error> {
+        if self.data::<Data>().paused {
+            return Err(PSP34Error::Custom(Shiden34Error::MintingPaused.as_str()))
+        }
+        Ok(())
+    }
+
+    /// Set new value for the baseUri unless metadata is frozen
+    fn update_base_uri(&mut self, uri: String) -> Result<(), PSP34Error> {
+        self.check_metadata_not_frozen()?;
+        let id = PSP34Impl::collection_id(self);
+        metadata::Internal::_set_attribute(self, id, String::from("baseUri"), uri);
+
+        Ok(())
+    }
+
+    /// Set share of the mint price credited to referrers, in basis points
+    fn update_referral_bps(&mut self, referral_bps: u16) -> Result<(), PSP34Error> {
+        if referral_bps > 10_000 {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::InvalidReferralShare.as_str(),
+            ))
+        }
+        self.data::<Data>().referral_bps = referral_bps;
+
+        Ok(())
+    }
+
+    /// Add a new sale phase after the existing ones
+    fn append_sale_phase(&mut self, phase: SalePhase) -> Result<(), PSP34Error> {
+        self.check_sale_phase(&phase)?;
+        let phase_id = self.data::<Data>().sale_phase_count;
+        self.data::<Data>().sale_phases.insert(&phase_id, &phase);
+        self.data::<Data>().sale_phase_count += 1;
+
+        Ok(())
+    }
+
+    /// Replace an existing sale phase
+    fn replace_sale_phase(&mut self, phase_id: u32, phase: SalePhase) -> Result<(), PSP34Error> {
+        if phase_id >= self.data::<Data>().sale_phase_count {
+            return Err(PSP34Error::Custom(Shiden34Error::InvalidSalePhase.as_str()))
+        }
+        self.check_sale_phase(&phase)?;
+        self.data::<Data>().sale_phases.insert(&phase_id, &phase);
+
+        Ok(())
+    }
+
+    /// Set Dutch auction pricing unless the auction already started
+    fn update_dutch_auction(&mut self, auction: Option<DutchAuction>) -> Result<(), PSP34Error> {
+        if self.data::<Data>().auction_total_minted > 0 {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::AuctionAlreadyStarted.as_str(),
+            ))
+        }
+        if let Some(auction) = &auction {
+            if auction.step_interval == 0 || auction.start_price < auction.floor_price {
+                return Err(PSP34Error::Custom(Shiden34Error::InvalidAuction.as_str()))
+            }
+        }
+        self.data::<Data>().dutch_auction = auction;
+
+        Ok(())
+    }
+
+    /// Set URI override of the token unless it is locked or metadata is frozen
+    fn update_token_uri(&mut self, token_id: u64, uri: Option<String>) -> Result<(), PSP34Error> {
+        self.check_metadata_not_frozen()?;
+        if self
+            .data::<Data>()
+            .locked_token_uris
+            .get(&token_id)
+            .unwrap_or(false)
+        {
+            return Err(PSP34Error::Custom(Shiden34Error::TokenUriLocked.as_str()))
+        }
+        match uri {
+            Some(uri) => {
+                self.data::<Data>().token_uris.insert(&token_id, &uri);
+            }
+            None => {
+                self.data::<Data>().token_uris.remove(&token_id);
+            }
+        }
+        self._emit_metadata_update_event(token_id);
+
+        Ok(())
+    }
+
+    /// Set the real baseUri and mark the collection as revealed
+    fn reveal_base_uri(&mut self, base_uri: String) -> Result<(), PSP34Error> {
+        self.check_metadata_not_frozen()?;
+        self.check_not_revealed()?;
+        let id = PSP34Impl::collection_id(self);
+        metadata::Internal::_set_attribute(self, id, String::from("baseUri"), base_uri.clone());
+        self.data::<Data>().revealed = true;
+        self._emit_revealed_event(base_uri);
+
+        Ok(())
+    }
+
+    /// Permanently close the collection at the number of minted tokens
+    fn close_supply(&mut self) -> Result<(), PSP34Error> {
+        self.check_max_supply_adjustable()?;
+        let max_supply = self.data::<Data>().last_token_id;
+        self.data::<Data>().max_supply = max_supply;
+        self.data::<Data>().collection_closed = true;
+        self._emit_collection_closed_event(max_supply);
+
+        Ok(())
+    }
+
+    /// Set price per token unless it is locked
+    fn update_price(&mut self, price_per_mint: Balance) -> Result<(), PSP34Error> {
+        if self.data::<Data>().price_locked {
+            return Err(PSP34Error::Custom(Shiden34Error::PriceLocked.as_str()))
+        }
+        let old_price = self.data::<Data>().price_per_mint;
+        self.data::<Data>().price_per_mint = price_per_mint;
+        self._emit_price_changed_event(old_price, price_per_mint);
+
+        Ok(())
+    }
+
+    /// Lower max supply of tokens. It can't go below the number of minted tokens
+    fn update_max_supply(&mut self, max_supply: u64) -> Result<(), PSP34Error> {
+        self.check_max_supply_adjustable()?;
+        let old_max_supply = self.data::<Data>().max_supply;
+        if max_supply > old_max_supply || max_supply < self.data::<Data>().last_token_id {
+            return Err(PSP34Error::Custom(Shiden34Error::InvalidMaxSupply.as_str()))
+        }
+        self.data::<Data>().max_supply = max_supply;
+        self._emit_max_supply_changed_event(old_max_supply, max_supply);
+
+        Ok(())
+    }
+
+    /// Check if max supply could still be changed. The starting index freezes it,
+    /// as token URIs depend on it
+    fn check_max_supply_adjustable(&self) -> Result<(), PSP34Error> {
+        // metadata of random ids is shuffled over the whole supply, so it can't be lowered
+        if self.data::<Data>().id_assignment == IdAssignment::Random {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::MaxSupplyFixedForRandomIds.as_str(),
+            ))
+        }
+        if self.data::<Data>().collection_closed {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::CollectionAlreadyClosed.as_str(),
+            ))
+        }
+        if self.data::<Data>().starting_index.is_some() {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::StartingIndexAlreadySet.as_str(),
+            ))
+        }
+        Ok(())
+    }
+
+    /// Check if metadata is not permanently frozen
+    fn check_metadata_not_frozen(&self) -> Result<(), PSP34Error> {
+        if self.data::<Data>().metadata_frozen {
+            return Err(PSP34Error::Custom(Shiden34Error::MetadataFrozen.as_str()))
+        }
+        Ok(())
+    }
+
+    /// Check if the collection is not revealed yet
+    fn check_not_revealed(&self) -> Result<(), PSP34Error> {
+        if self.data::<Data>().revealed {
+            return Err(PSP34Error::Custom(Shiden34Error::AlreadyRevealed.as_str()))
+        }
+        Ok(())
+    }
+
+    /// Check if public minting is open, i.e. no allowlist is active
+    fn check_public_mint(&self) -> Result<(), PSP34Error> {
+        if self.data::<Data>().allowlist_root.is_some() {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::PublicMintNotActive.as_str(),
+            ))
+        }
+        Ok(())
+    }
+
+    /// Check if the Merkle proof of (account, allocation) leads to the allowlist root
+    fn check_allowlist_proof(
+        &self,
+        account: AccountId,
+        allocation: u64,
+        proof: &[[u8; 32]],
+    ) -> Result<(), PSP34Error> {
+        let root = self
+            .data::<Data>()
+            .allowlist_root
+            .ok_or(PSP34Error::Custom(
+                Shiden34Error::InvalidMerkleProof.as_str(),
+            ))?;
+
+        let mut node = [0u8; 32];
+        ink::env::hash_encoded::<Keccak256, _>(&(account, allocation), &mut node);
+        for sibling in proof {
+            node = hash_pair(&node, sibling);
+        }
+
+        if node != root {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::InvalidMerkleProof.as_str(),
+            ))
+        }
+        Ok(())
+    }
+
+    /// Get price per token from the Dutch auction, the active sale phase, or the default price
+    fn mint_price(&self) -> Result<Balance, PSP34Error> {
+        if let Some(auction) = &self.data::<Data>().dutch_auction {
+            return auction
+                .price_at(Self::env().block_timestamp())
+                .ok_or(PSP34Error::Custom(
+                    Shiden34Error::AuctionNotStarted.as_str(),
+                ))
+        }
+        if self.data::<Data>().sale_phase_count == 0 {
+            return Ok(self.data::<Data>().price_per_mint)
+        }
+        self.active_sale_phase()
+            .map(|(_, phase)| phase.price_per_mint)
+            .ok_or(PSP34Error::Custom(
+                Shiden34Error::NoActiveSalePhase.as_str(),
+            ))
+    }
+
+    /// Get id and data of the sale phase running at the current block timestamp
+    fn active_sale_phase(&self) -> Option<(u32, SalePhase)> {
+        let now = Self::env().block_timestamp();
+        (0..self.data::<Data>().sale_phase_count).find_map(|phase_id| {
+            self.data::<Data>()
+                .sale_phases
+                .get(&phase_id)
+                .filter(|phase| phase.start <= now && now < phase.end)
+                .map(|phase| (phase_id, phase))
+        })
+    }
+
+    /// Check if the collection is sold out or all sale phases are over
+    fn sale_ended(&self) -> bool {
+        if self.data::<Data>().last_token_id >= self.data::<Data>().max_supply {
+            return true
+        }
+        let now = Self::env().block_timestamp();
+        let phase_count = self.data::<Data>().sale_phase_count;
+        phase_count > 0
+            && (0..phase_count).all(|phase_id| {
+                self.data::<Data>()
+                    .sale_phases
+                    .get(&phase_id)
+                    .map_or(true, |phase| phase.end <= now)
+            })
+    }
+
+    /// Check if sale phase parameters are valid
+    fn check_sale_phase(&self, phase: &SalePhase) -> Result<(), PSP34Error> {
+        if phase.start >= phase.end {
+            return Err(PSP34Error::Custom(Shiden34Error::InvalidSalePhase.as_str()))
+        }
+        Ok(())
+    }
+
+    /// Check supply and wallet limits of the active sale phase and record the minted tokens
+    fn use_sale_phase(&mut self, account: AccountId, mint_amount: u64) -> Result<(), PSP34Error> {
+        if self.data::<Data>().sale_phase_count == 0 {
+            return Ok(())
+        }
+        let (phase_id, phase) = self.active_sale_phase().ok_or(PSP34Error::Custom(
+            Shiden34Error::NoActiveSalePhase.as_str(),
+        ))?;
+
+        let phase_minted = self
+            .data::<Data>()
+            .sale_phase_minted
+            .get(&phase_id)
+            .unwrap_or_default()
+            .checked_add(mint_amount)
+            .filter(|minted| *minted <= phase.max_supply)
+            .ok_or(PSP34Error::Custom(Shiden34Error::SalePhaseSoldOut.as_str()))?;
+        let wallet_minted = self
+            .data::<Data>()
+            .sale_phase_wallet_minted
+            .get(&(phase_id, account))
+            .unwrap_or_default()
+            .checked_add(mint_amount)
+            .filter(|minted| *minted <= phase.max_per_wallet)
+            .ok_or(PSP34Error::Custom(
+                Shiden34Error::WalletLimitReached.as_str(),
+            ))?;
+
+        self.data::<Data>()
+            .sale_phase_minted
+            .insert(&phase_id, &phase_minted);
+        self.data::<Data>()
+            .sale_phase_wallet_minted
+            .insert(&(phase_id, account), &wallet_minted);
+        Ok(())
+    }
+
+    /// Check lifetime limits of the caller (and the recipient, if configured) and record the minted tokens
+    fn use_wallet_limit(
+        &mut self,
+        caller: AccountId,
+        to: AccountId,
+        mint_amount: u64,
+    ) -> Result<(), PSP34Error> {
+        let caller_minted = self.check_wallet_limit(caller, mint_amount)?;
+        if self.data::<Data>().wallet_limit_counts_recipient && to != caller {
+            let recipient_minted = self.check_wallet_limit(to, mint_amount)?;
+            self.data::<Data>()
+                .wallet_minted
+                .insert(&to, &recipient_minted);
+        }
+        self.data::<Data>()
+            .wallet_minted
+            .insert(&caller, &caller_minted);
+        Ok(())
+    }
+
+    /// Check if the account could mint `mint_amount` more tokens and return its new minted count
+    fn check_wallet_limit(&self, account: AccountId, mint_amount: u64) -> Result<u64, PSP34Error> {
+        let max_per_wallet = self.data::<Data>().max_per_wallet;
+        self.data::<Data>()
+            .wallet_minted
+            .get(&account)
+            .unwrap_or_default()
+            .checked_add(mint_amount)
+            .filter(|minted| max_per_wallet.map_or(true, |max| *minted <= max))
+            .ok_or(PSP34Error::Custom(
+                Shiden34Error::WalletLimitReached.as_str(),
+            ))
+    }
+
+    /// Record tokens paid for at the Dutch auction price, so rebates could be claimed later
+    fn record_auction_mint(
+        &mut self,
+        account: AccountId,
+        mint_amount: u64,
+    ) -> Result<(), PSP34Error> {
+        let rebate = match &self.data::<Data>().dutch_auction {
+            Some(auction) => auction.rebate,
+            None => return Ok(()),
+        };
+        let price = self.mint_price()?;
+        let paid = price.saturating_mul(mint_amount as Balance);
+
+        self.data::<Data>().auction_clearing_price = price;
+        self.data::<Data>().auction_total_paid += paid;
+        self.data::<Data>().auction_total_minted += mint_amount;
+        if rebate {
+            let account_paid = self
+                .data::<Data>()
+                .auction_paid
+                .get(&account)
+                .unwrap_or_default();
+            let account_minted = self
+                .data::<Data>()
+                .auction_minted
+                .get(&account)
+                .unwrap_or_default();
+            self.data::<Data>()
+                .auction_paid
+                .insert(&account, &(account_paid + paid));
+            self.data::<Data>()
+                .auction_minted
+                .insert(&account, &(account_minted + mint_amount));
+        }
+        Ok(())
+    }
+
+    /// Check if the Dutch auction reached its floor price or the collection is sold out
+    fn auction_ended(&self, auction: &DutchAuction) -> bool {
+        self.data::<Data>().last_token_id >= self.data::<Data>().max_supply
+            || auction.price_at(Self::env().block_timestamp()) == Some(auction.floor_price)
+    }
+
+    /// Get account receiving withdrawn funds: treasury, or contract owner if no treasury is set
+    fn withdrawal_recipient(&self) -> Result<AccountId, PSP34Error> {
+        self.data::<Data>()
+            .treasury
+            .or_else(|| self.data::<ownable::Data>().owner.get().flatten())
+            .ok_or(PSP34Error::Custom(
+                Shiden34Error::NoWithdrawalRecipient.as_str(),
+            ))
+    }
+
+    /// Get contract balance which can be withdrawn. Fails once payees share the funds
+    fn withdrawable_balance(&self) -> Result<Balance, PSP34Error> {
+        if self.data::<payment_splitter::types::Data>().total_shares > 0 {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::PaymentSplitterActive.as_str(),
+            ))
+        }
+        Ok(Self::env()
+            .balance()
+            .checked_sub(Self::env().minimum_balance())
+            .unwrap_or_default()
+            .saturating_sub(self.reserved_balance()))
+    }
+
+    /// Queue operation to be executed once the timelock delay passes
+    fn queue_operation(&mut self, operation: TimelockOperation) -> Result<(), PSP34Error> {
+        let operation_id = self.data::<Data>().timelocked_operation_count;
+        let eta = Self::env()
+            .block_timestamp()
+            .saturating_add(self.data::<Data>().timelock_delay);
+        self.data::<Data>()
+            .timelocked_operations
+            .insert(&operation_id, &TimelockedOperation { operation, eta });
+        self.data::<Data>().pending_operation_ids.push(operation_id);
+        self.data::<Data>().timelocked_operation_count = operation_id + 1;
+        self._emit_operation_queued_event(operation_id, eta);
+
+        Ok(())
+    }
+
+    /// Remove queued operation and return it
+    fn take_timelocked_operation(
+        &mut self,
+        operation_id: u32,
+    ) -> Result<TimelockedOperation, PSP34Error> {
+        let timelocked = self
+            .data::<Data>()
+            .timelocked_operations
+            .get(&operation_id)
+            .ok_or(PSP34Error::Custom(Shiden34Error::UnknownOperation.as_str()))?;
+        self.data::<Data>()
+            .timelocked_operations
+            .remove(&operation_id);
+        self.data::<Data>()
+            .pending_operation_ids
+            .retain(|id| *id != operation_id);
+        Ok(timelocked)
+    }
+
+    /// Cancel all queued operations
+    fn cancel_pending_operations(&mut self) {
+        for operation_id in core::mem::take(&mut self.data::<Data>().pending_operation_ids) {
+            self.data::<Data>()
+                .timelocked_operations
+                .remove(&operation_id);
+            self._emit_operation_cancelled_event(operation_id);
+        }
+    }
+
+    /// Withdraw all withdrawable funds to treasury, or to contract owner if no treasury is set
+    fn withdraw_balance(&mut self) -> Result<(), PSP34Error> {
+        let recipient = self.withdrawal_recipient()?;
+        let amount = self.withdrawable_balance()?;
+        self.transfer_withdrawal(recipient, amount)
+    }
+
+    /// Withdraw `amount` of funds to `recipient`
+    fn withdraw_balance_to(
+        &mut self,
+        recipient: AccountId,
+        amount: Balance,
+    ) -> Result<(), PSP34Error> {
+        if amount > self.withdrawable_balance()? {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::InsufficientWithdrawableBalance.as_str(),
+            ))
+        }
+        self.transfer_withdrawal(recipient, amount)
+    }
+
+    /// Withdraw PSP22 token balance to treasury, or to contract owner if no treasury is set
+    fn withdraw_psp22_balance(&mut self, token: AccountId) -> Result<(), PSP34Error> {
+        let recipient = self.withdrawal_recipient()?;
+        self.withdraw_psp22_balance_to(token, recipient)
+    }
+
+    /// Withdraw PSP22 token balance to `recipient`
+    fn withdraw_psp22_balance_to(
+        &mut self,
+        token: AccountId,
+        recipient: AccountId,
+    ) -> Result<(), PSP34Error> {
+        if self.data::<payment_splitter::types::Data>().total_shares > 0 {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::PaymentSplitterActive.as_str(),
+            ))
+        }
+        let balance = PSP22Ref::balance_of(&token, Self::env().account_id());
+        PSP22Ref::transfer(&token, recipient, balance, Vec::new())
+            .map_err(|_| PSP34Error::Custom(Shiden34Error::WithdrawalFailed.as_str()))?;
+        Ok(())
+    }
+
+    /// Transfer withdrawn funds and emit event
+    fn transfer_withdrawal(&mut self, to: AccountId, amount: Balance) -> Result<(), PSP34Error> {
+        Self::env()
+            .transfer(to, amount)
+            .map_err(|_| PSP34Error::Custom(Shiden34Error::WithdrawalFailed.as_str()))?;
+        self._emit_withdrawn_event(to, amount);
+        Ok(())
+    }
+
+    /// Get balance owed to auction rebates and referral rewards
+    fn reserved_balance(&self) -> Balance {
+        self.auction_rebate_reserve()
+            .saturating_add(self.data::<Data>().referral_rewards_total)
+    }
+
+    /// Get balance held back from withdrawal for Dutch auction rebates.
+    /// Until the collection sells out the clearing price could still drop to the floor price
+    fn auction_rebate_reserve(&self) -> Balance {
+        let auction = match &self.data::<Data>().dutch_auction {
+            Some(auction) if auction.rebate => auction,
+            _ => return 0,
+        };
+        let lowest_price = if self.data::<Data>().last_token_id >= self.data::<Data>().max_supply {
+            self.data::<Data>().auction_clearing_price
+        } else {
+            auction.floor_price
+        };
+        self.data::<Data>().auction_total_paid.saturating_sub(
+            lowest_price.saturating_mul(self.data::<Data>().auction_total_minted as Balance),
+        )
+    }
+
+    /// Emit event when value transferred above the mint price is refunded
+    fn _emit_refund_event(&self, _to: AccountId, _amount: Balance) {}
+
+    /// Emit event when price per token is changed
+    fn _emit_price_changed_event(&self, _old: Balance, _new: Balance) {}
+
+    /// Emit event when funds are withdrawn
+    fn _emit_withdrawn_event(&self, _to: AccountId, _amount: Balance) {}
+
+    /// Emit event when new contract owner is proposed
+    fn _emit_ownership_proposed_event(&self, _pending_owner: AccountId) {}
+
+    /// Emit event when proposed owner accepts ownership
+    fn _emit_ownership_accepted_event(
+        &self,
+        _previous_owner: Option<AccountId>,
+        _new_owner: AccountId,
+    ) {
+    }
+
+    /// Emit event when pending ownership transfer is cancelled
+    fn _emit_ownership_transfer_cancelled_event(&self, _pending_owner: AccountId) {}
+
+    /// Emit event when operation is queued behind the timelock
+    fn _emit_operation_queued_event(&self, _operation_id: u32, _eta: Timestamp) {}
+
+    /// Emit event when queued operation is executed
+    fn _emit_operation_executed_event(&self, _operation_id: u32) {}
+
+    /// Emit event when queued operation is cancelled
+    fn _emit_operation_cancelled_event(&self, _operation_id: u32) {}
+
+    /// Emit event when max supply is lowered
+    fn _emit_max_supply_changed_event(&self, _old: u64, _new: u64) {}
+
+    /// Emit event when the collection is permanently closed
+    fn _emit_collection_closed_event(&self, _max_supply: u64) {}
+
+    /// Emit event when the collection is revealed
+    fn _emit_revealed_event(&self, _base_uri: String) {}
+
+    /// Emit event when URI of the token changes
+    fn _emit_metadata_update_event(&self, _token_id: u64) {}
+
+    /// Emit event when metadata is permanently frozen
+    fn _emit_permanent_uri_event(&self, _base_uri: String) {}
+
+    /// Emit event when minting is paused
+    fn _emit_paused_event(&self, _account: AccountId) {}
+
+    /// Emit event when minting is unpaused
+    fn _emit_unpaused_event(&self, _account: AccountId) {}
+
+    /// Get Keccak-256 hash of the SCALE encoded (contract, to, mint_amount, price_per_mint, nonce, expiry)
+    /// which the voucher signer signs
+    fn voucher_hash(
+        &self,
+        to: AccountId,
+        mint_amount: u64,
+        price_per_mint: Balance,
+        nonce: u64,
+        expiry: Timestamp,
+    ) -> [u8; 32] {
+        let mut message_hash = [0u8; 32];
+        ink::env::hash_encoded::<Keccak256, _>(
+            &(
+                Self::env().account_id(),
+                to,
+                mint_amount,
+                price_per_mint,
+                nonce,
+                expiry,
+            ),
+            &mut message_hash,
+        );
+        message_hash
+    }
+
+    /// Check if the signature over the message hash is made by the voucher signer
+    fn check_voucher_signature(
+        &self,
+        message_hash: &[u8; 32],
+        signature: &[u8; 65],
+    ) -> Result<(), PSP34Error> {
+        let signer = self
+            .data::<Data>()
+            .voucher_signer
+            .ok_or(PSP34Error::Custom(
+                Shiden34Error::InvalidVoucherSignature.as_str(),
+            ))?;
+        let mut public_key = [0u8; 33];
+        ink::env::ecdsa_recover(signature, message_hash, &mut public_key)
+            .map_err(|_| PSP34Error::Custom(Shiden34Error::InvalidVoucherSignature.as_str()))?;
+
+        if public_key != signer {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::InvalidVoucherSignature.as_str(),
+            ))
+        }
+        Ok(())
+    }
+
+    /// Mint `mint_amount` tokens to `to`
+    fn mint_tokens(&mut self, to: AccountId, mint_amount: u64) -> Result<(), PSP34Error> {
+        for _ in 0..mint_amount {
+            let token_id = self.next_token_id()?;
+            self._mint_to(to, Id::U64(token_id))?;
+            self.data::<Data>().last_token_id = token_id;
+        }
+
+        Ok(())
+    }
+
+    /// Get id for the next token to be minted
+    fn next_token_id(&self) -> Result<u64, PSP34Error> {
+        // the revealed seed would make the metadata of later tokens known before minting
+        if self.data::<Data>().id_assignment == IdAssignment::Random
+            && self.data::<Data>().random_seed.is_some()
+        {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::RandomSeedAlreadyRevealed.as_str(),
+            ))
+        }
+        // first mint id is 1
+        self.data::<Data>()
+            .last_token_id
+            .checked_add(1)
+            .ok_or(PSP34Error::Custom(Shiden34Error::CollectionIsFull.as_str()))
+    }
+}
+
+/// Hash two Merkle tree nodes, ordered so that proofs don't need position flags
+pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let mut pair = [0u8; 64];
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    pair[..32].copy_from_slice(first);
+    pair[32..].copy_from_slice(second);
+
+    let mut output = [0u8; 32];
+    ink::env::hash_bytes::<Keccak256>(&pair, &mut output);
+    output
+}
+
+/// Map `token_id` from `1..=max_supply` to a metadata id in the same range.
+/// The mapping is a permutation keyed by `seed`: a Feistel network over the smallest
+/// even bit width holding `max_supply`, applied again until the result is in range
+pub fn shuffled_id(seed: &[u8; 32], token_id: u64, max_supply: u64) -> u64 {
+    let bits = 64 - max_supply.saturating_sub(1).leading_zeros();
+    let half_bits = ((bits + 1) / 2).max(1);
+    let mask = (1u64 << half_bits) - 1;
+
+    let mut index = token_id - 1;
+    loop {
+        let mut left = index >> half_bits;
+        let mut right = index & mask;
+        for round in 0..FEISTEL_ROUNDS {
+            let mut output = [0u8; 32];
+            ink::env::hash_encoded::<Keccak256, _>(&(seed, round, right), &mut output);
+            let mut round_bytes = [0u8; 8];
+            round_bytes.copy_from_slice(&output[..8]);
+            (left, right) = (right, left ^ (u64::from_le_bytes(round_bytes) & mask));
+        }
+        index = (left << half_bits) | right;
+        if index < max_supply {
+            return index + 1
+        }
+    }
 }
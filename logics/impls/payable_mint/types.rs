@@ -1,8 +1,33 @@
-use openbrush::traits::{
-    Balance,
-    String,
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::access_control::RoleType,
+    storage::Mapping,
+    traits::{
+        AccountId,
+        Balance,
+        String,
+        Timestamp,
+    },
 };
 
+/// Max number of tokens which could be airdropped per call
+pub const MAX_AIRDROP_AMOUNT: u64 = 100;
+
+/// Max timelock delay in milliseconds, 30 days
+pub const MAX_TIMELOCK_DELAY: Timestamp = 30 * 24 * 60 * 60 * 1000;
+
+/// Number of Feistel rounds shuffling metadata of tokens with random ids
+pub const FEISTEL_ROUNDS: u8 = 4;
+
+/// Role managing sale configuration
+pub const ADMIN: RoleType = ink::selector_id!("ADMIN");
+/// Role minting tokens free of charge
+pub const MINTER: RoleType = ink::selector_id!("MINTER");
+/// Role managing token metadata
+pub const METADATA_MANAGER: RoleType = ink::selector_id!("METADATA_MANAGER");
+/// Role withdrawing collected funds
+pub const TREASURER: RoleType = ink::selector_id!("TREASURER");
+
 #[derive(Default, Debug)]
 #[openbrush::storage_item]
 pub struct Data {
@@ -11,6 +36,139 @@ pub struct Data {
     pub max_supply: u64,
     pub price_per_mint: Balance,
     pub max_amount: u64,
+    pub allowlist_root: Option<[u8; 32]>,
+    pub allowlist_minted: Mapping<AccountId, u64>,
+    pub sale_phase_count: u32,
+    pub sale_phases: Mapping<u32, SalePhase>,
+    pub sale_phase_minted: Mapping<u32, u64>,
+    pub sale_phase_wallet_minted: Mapping<(u32, AccountId), u64>,
+    pub max_per_wallet: Option<u64>,
+    pub wallet_limit_counts_recipient: bool,
+    pub wallet_minted: Mapping<AccountId, u64>,
+    pub dutch_auction: Option<DutchAuction>,
+    pub auction_clearing_price: Balance,
+    pub auction_total_paid: Balance,
+    pub auction_total_minted: u64,
+    pub auction_paid: Mapping<AccountId, Balance>,
+    pub auction_minted: Mapping<AccountId, u64>,
+    pub paused: bool,
+    pub id_assignment: IdAssignment,
+    pub random_seed_commitment: Option<[u8; 32]>,
+    pub random_seed: Option<[u8; 32]>,
+    pub psp22_token: Option<AccountId>,
+    pub psp22_price_per_mint: Balance,
+    pub refund_excess: bool,
+    pub voucher_signer: Option<[u8; 33]>,
+    pub used_voucher_nonces: Mapping<u64, bool>,
+    pub placeholder_uri: Option<String>,
+    pub revealed: bool,
+    pub provenance_hash: Option<[u8; 32]>,
+    pub starting_index_commitment: Option<[u8; 32]>,
+    pub starting_index: Option<u64>,
+    pub referral_bps: u16,
+    pub referral_rewards: Mapping<AccountId, Balance>,
+    pub referral_rewards_total: Balance,
+    pub price_locked: bool,
+    pub collection_closed: bool,
+    pub treasury: Option<AccountId>,
+    pub pending_owner: Option<AccountId>,
+    pub timelock_delay: Timestamp,
+    pub timelocked_operation_count: u32,
+    pub timelocked_operations: Mapping<u32, TimelockedOperation>,
+    pub pending_operation_ids: Vec<u32>,
+    pub token_uris: Mapping<u64, String>,
+    pub locked_token_uris: Mapping<u64, bool>,
+    pub metadata_frozen: bool,
+}
+
+/// Operation which is queued while the timelock delay is set
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum TimelockOperation {
+    /// Withdraw all funds to the treasury, or contract owner, set when it was queued
+    Withdraw(AccountId),
+    /// Withdraw amount of funds to the recipient
+    WithdrawTo(AccountId, Balance),
+    /// Set new value for the baseUri
+    SetBaseUri(String),
+    /// Reveal the collection by setting the real baseUri
+    Reveal(String),
+    /// Set max number of tokens which could be minted per call
+    SetMaxMintAmount(u64),
+    /// Withdraw PSP22 token balance to the treasury, or contract owner, set when it was queued
+    WithdrawPsp22(AccountId, AccountId),
+    /// Add a payee with its share weight
+    AddPayee(AccountId, u128),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct TimelockedOperation {
+    pub operation: TimelockOperation,
+    /// Earliest time the operation could be executed at
+    pub eta: Timestamp,
+}
+
+/// How ids are assigned to newly minted tokens
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum IdAssignment {
+    /// Ids follow `last_token_id + 1`
+    #[default]
+    Sequential,
+    /// Ids follow `last_token_id + 1`, and metadata is shuffled by a seed revealed
+    /// once the sale is over
+    Random,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct SalePhase {
+    pub name: String,
+    pub price_per_mint: Balance,
+    pub max_supply: u64,
+    pub max_per_wallet: u64,
+    pub start: Timestamp,
+    pub end: Timestamp,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct DutchAuction {
+    pub start_price: Balance,
+    pub floor_price: Balance,
+    pub price_step: Balance,
+    pub step_interval: Timestamp,
+    pub start: Timestamp,
+    pub rebate: bool,
+}
+
+impl DutchAuction {
+    /// Price at the given timestamp, `None` if the auction has not started yet
+    pub fn price_at(&self, now: Timestamp) -> Option<Balance> {
+        let elapsed_steps = now.checked_sub(self.start)? / self.step_interval;
+        let decrease = self.price_step.saturating_mul(elapsed_steps as Balance);
+        Some(
+            self.start_price
+                .saturating_sub(decrease)
+                .max(self.floor_price),
+        )
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -21,6 +179,68 @@ pub enum Shiden34Error {
     CollectionIsFull,
     TooManyTokensToMint,
     WithdrawalFailed,
+    InvalidMerkleProof,
+    AllocationExhausted,
+    PublicMintNotActive,
+    InvalidSalePhase,
+    NoActiveSalePhase,
+    SalePhaseSoldOut,
+    WalletLimitReached,
+    InvalidAuction,
+    AuctionNotStarted,
+    AuctionAlreadyStarted,
+    AuctionNotEnded,
+    NoRebateToClaim,
+    MintingPaused,
+    MintingNotPaused,
+    AirdropTooLarge,
+    RandomSeedNotRevealed,
+    InvalidRandomSeed,
+    Psp22PaymentFailed,
+    RefundFailed,
+    InvalidVoucherSignature,
+    VoucherExpired,
+    VoucherAlreadyUsed,
+    AlreadyRevealed,
+    ProvenanceFrozen,
+    SaleNotEnded,
+    StartingIndexAlreadySet,
+    SelfReferral,
+    InvalidReferralShare,
+    NoReferralRewards,
+    PriceLocked,
+    InvalidMaxSupply,
+    CollectionAlreadyClosed,
+    InvalidShares,
+    PayeeAlreadyExists,
+    PaymentSplitterLocked,
+    NoPaymentDue,
+    PaymentSplitterActive,
+    InvalidRoyalty,
+    NoWithdrawalRecipient,
+    InsufficientWithdrawableBalance,
+    NoPendingOwner,
+    CallerIsNotPendingOwner,
+    InvalidMultisigConfig,
+    MultisigAlreadyConfigured,
+    MultisigRequired,
+    NotMultisigSigner,
+    UnknownProposal,
+    ProposalExpired,
+    ProposalAlreadyExecuted,
+    AlreadyApproved,
+    ThresholdNotReached,
+    UnknownOperation,
+    TimelockNotExpired,
+    InvalidTimelockDelay,
+    TokenUriLocked,
+    MetadataFrozen,
+    Psp22PaymentUnavailable,
+    MaxSupplyFixedForRandomIds,
+    NotRevealed,
+    StartingIndexNotSet,
+    RandomSeedAlreadyRevealed,
+    InvalidStartingIndexSeed,
 }
 
 impl Shiden34Error {
@@ -31,6 +251,70 @@ impl Shiden34Error {
             Shiden34Error::CollectionIsFull => String::from("CollectionIsFull"),
             Shiden34Error::TooManyTokensToMint => String::from("TooManyTokensToMint"),
             Shiden34Error::WithdrawalFailed => String::from("WithdrawalFailed"),
+            Shiden34Error::InvalidMerkleProof => String::from("InvalidMerkleProof"),
+            Shiden34Error::AllocationExhausted => String::from("AllocationExhausted"),
+            Shiden34Error::PublicMintNotActive => String::from("PublicMintNotActive"),
+            Shiden34Error::InvalidSalePhase => String::from("InvalidSalePhase"),
+            Shiden34Error::NoActiveSalePhase => String::from("NoActiveSalePhase"),
+            Shiden34Error::SalePhaseSoldOut => String::from("SalePhaseSoldOut"),
+            Shiden34Error::WalletLimitReached => String::from("WalletLimitReached"),
+            Shiden34Error::InvalidAuction => String::from("InvalidAuction"),
+            Shiden34Error::AuctionNotStarted => String::from("AuctionNotStarted"),
+            Shiden34Error::AuctionAlreadyStarted => String::from("AuctionAlreadyStarted"),
+            Shiden34Error::AuctionNotEnded => String::from("AuctionNotEnded"),
+            Shiden34Error::NoRebateToClaim => String::from("NoRebateToClaim"),
+            Shiden34Error::MintingPaused => String::from("MintingPaused"),
+            Shiden34Error::MintingNotPaused => String::from("MintingNotPaused"),
+            Shiden34Error::AirdropTooLarge => String::from("AirdropTooLarge"),
+            Shiden34Error::RandomSeedNotRevealed => String::from("RandomSeedNotRevealed"),
+            Shiden34Error::InvalidRandomSeed => String::from("InvalidRandomSeed"),
+            Shiden34Error::Psp22PaymentFailed => String::from("Psp22PaymentFailed"),
+            Shiden34Error::RefundFailed => String::from("RefundFailed"),
+            Shiden34Error::InvalidVoucherSignature => String::from("InvalidVoucherSignature"),
+            Shiden34Error::VoucherExpired => String::from("VoucherExpired"),
+            Shiden34Error::VoucherAlreadyUsed => String::from("VoucherAlreadyUsed"),
+            Shiden34Error::AlreadyRevealed => String::from("AlreadyRevealed"),
+            Shiden34Error::ProvenanceFrozen => String::from("ProvenanceFrozen"),
+            Shiden34Error::SaleNotEnded => String::from("SaleNotEnded"),
+            Shiden34Error::StartingIndexAlreadySet => String::from("StartingIndexAlreadySet"),
+            Shiden34Error::SelfReferral => String::from("SelfReferral"),
+            Shiden34Error::InvalidReferralShare => String::from("InvalidReferralShare"),
+            Shiden34Error::NoReferralRewards => String::from("NoReferralRewards"),
+            Shiden34Error::PriceLocked => String::from("PriceLocked"),
+            Shiden34Error::InvalidMaxSupply => String::from("InvalidMaxSupply"),
+            Shiden34Error::CollectionAlreadyClosed => String::from("CollectionAlreadyClosed"),
+            Shiden34Error::InvalidShares => String::from("InvalidShares"),
+            Shiden34Error::PayeeAlreadyExists => String::from("PayeeAlreadyExists"),
+            Shiden34Error::PaymentSplitterLocked => String::from("PaymentSplitterLocked"),
+            Shiden34Error::NoPaymentDue => String::from("NoPaymentDue"),
+            Shiden34Error::PaymentSplitterActive => String::from("PaymentSplitterActive"),
+            Shiden34Error::InvalidRoyalty => String::from("InvalidRoyalty"),
+            Shiden34Error::NoWithdrawalRecipient => String::from("NoWithdrawalRecipient"),
+            Shiden34Error::InsufficientWithdrawableBalance => {
+                String::from("InsufficientWithdrawableBalance")
+            }
+            Shiden34Error::NoPendingOwner => String::from("NoPendingOwner"),
+            Shiden34Error::CallerIsNotPendingOwner => String::from("CallerIsNotPendingOwner"),
+            Shiden34Error::InvalidMultisigConfig => String::from("InvalidMultisigConfig"),
+            Shiden34Error::MultisigAlreadyConfigured => String::from("MultisigAlreadyConfigured"),
+            Shiden34Error::MultisigRequired => String::from("MultisigRequired"),
+            Shiden34Error::NotMultisigSigner => String::from("NotMultisigSigner"),
+            Shiden34Error::UnknownProposal => String::from("UnknownProposal"),
+            Shiden34Error::ProposalExpired => String::from("ProposalExpired"),
+            Shiden34Error::ProposalAlreadyExecuted => String::from("ProposalAlreadyExecuted"),
+            Shiden34Error::AlreadyApproved => String::from("AlreadyApproved"),
+            Shiden34Error::ThresholdNotReached => String::from("ThresholdNotReached"),
+            Shiden34Error::UnknownOperation => String::from("UnknownOperation"),
+            Shiden34Error::TimelockNotExpired => String::from("TimelockNotExpired"),
+            Shiden34Error::InvalidTimelockDelay => String::from("InvalidTimelockDelay"),
+            Shiden34Error::TokenUriLocked => String::from("TokenUriLocked"),
+            Shiden34Error::MetadataFrozen => String::from("MetadataFrozen"),
+            Shiden34Error::Psp22PaymentUnavailable => String::from("Psp22PaymentUnavailable"),
+            Shiden34Error::MaxSupplyFixedForRandomIds => String::from("MaxSupplyFixedForRandomIds"),
+            Shiden34Error::NotRevealed => String::from("NotRevealed"),
+            Shiden34Error::StartingIndexNotSet => String::from("StartingIndexNotSet"),
+            Shiden34Error::RandomSeedAlreadyRevealed => String::from("RandomSeedAlreadyRevealed"),
+            Shiden34Error::InvalidStartingIndexSeed => String::from("InvalidStartingIndexSeed"),
         }
     }
 }
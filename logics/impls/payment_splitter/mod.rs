@@ -0,0 +1,2 @@
+pub mod payment_splitter;
+pub mod types;
@@ -0,0 +1,246 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use ink::prelude::vec::Vec;
+
+use crate::impls::{
+    multisig,
+    payable_mint,
+    payable_mint::types::{
+        Shiden34Error,
+        TimelockOperation,
+    },
+    payment_splitter::types::{
+        Data,
+        MAX_TOTAL_SHARES,
+    },
+};
+use openbrush::{
+    contracts::{
+        ownable,
+        ownable::only_owner,
+        psp22::PSP22Ref,
+        psp34::PSP34Error,
+        reentrancy_guard,
+        reentrancy_guard::non_reentrant,
+    },
+    modifiers,
+    traits::{
+        AccountId,
+        Balance,
+        Storage,
+    },
+};
+
+#[openbrush::trait_definition]
+pub trait PaymentSplitterImpl:
+    Storage<Data>
+    + Storage<reentrancy_guard::Data>
+    + Storage<ownable::Data>
+    + Internal
+    + multisig::multisig::Internal
+    + payable_mint::payable_mint::Internal
+{
+    /// Add a payee with its share weight. Payees are locked once native or PSP22 funds
+    /// are released. Queued while the timelock delay is set
+    ///
+    /// The sum of all shares can't exceed `MAX_TOTAL_SHARES`
+    #[ink(message)]
+    #[modifiers(only_owner)]
+    fn add_payee(&mut self, account: AccountId, shares: u128) -> Result<(), PSP34Error> {
+        self.check_multisig_inactive()?;
+        if self.data::<payable_mint::types::Data>().timelock_delay > 0 {
+            return self.queue_operation(TimelockOperation::AddPayee(account, shares))
+        }
+        self._add_payee(account, shares)
+    }
+
+    /// Transfer to the payee its pro-rata part of the received funds, minus what it was already released
+    #[ink(message)]
+    #[modifiers(non_reentrant)]
+    fn release(&mut self, account: AccountId) -> Result<(), PSP34Error> {
+        let payment = self.releasable(account);
+        if payment == 0 {
+            return Err(PSP34Error::Custom(Shiden34Error::NoPaymentDue.as_str()))
+        }
+
+        let released = self.released(account);
+        self.data::<Data>()
+            .released
+            .insert(&account, &(released + payment));
+        self.data::<Data>().total_released += payment;
+        self.data::<Data>().locked = true;
+
+        Self::env()
+            .transfer(account, payment)
+            .map_err(|_| PSP34Error::Custom(Shiden34Error::WithdrawalFailed.as_str()))?;
+        Ok(())
+    }
+
+    /// Transfer to the payee its pro-rata part of the received PSP22 `token` funds,
+    /// minus what it was already released
+    #[ink(message)]
+    #[modifiers(non_reentrant)]
+    fn release_psp22(&mut self, token: AccountId, account: AccountId) -> Result<(), PSP34Error> {
+        let payment = self.psp22_releasable(token, account);
+        if payment == 0 {
+            return Err(PSP34Error::Custom(Shiden34Error::NoPaymentDue.as_str()))
+        }
+
+        let released = self.psp22_released(token, account);
+        self.data::<Data>()
+            .psp22_released
+            .insert(&(token, account), &(released + payment));
+        let total_released = self.psp22_total_released(token);
+        self.data::<Data>()
+            .psp22_total_released
+            .insert(&token, &(total_released + payment));
+        self.data::<Data>().locked = true;
+
+        PSP22Ref::transfer(&token, account, payment, Vec::new())
+            .map_err(|_| PSP34Error::Custom(Shiden34Error::WithdrawalFailed.as_str()))?;
+        Ok(())
+    }
+
+    /// Get all payees
+    #[ink(message)]
+    fn payees(&self) -> Vec<AccountId> {
+        self.data::<Data>().payees.clone()
+    }
+
+    /// Get sum of share weights of all payees
+    #[ink(message)]
+    fn total_shares(&self) -> u128 {
+        self.data::<Data>().total_shares
+    }
+
+    /// Get share weight of the payee
+    #[ink(message)]
+    fn shares(&self, account: AccountId) -> u128 {
+        self.data::<Data>().shares.get(&account).unwrap_or_default()
+    }
+
+    /// Get funds released to all payees
+    #[ink(message)]
+    fn total_released(&self) -> Balance {
+        self.data::<Data>().total_released
+    }
+
+    /// Get funds released to the payee
+    #[ink(message)]
+    fn released(&self, account: AccountId) -> Balance {
+        self.data::<Data>()
+            .released
+            .get(&account)
+            .unwrap_or_default()
+    }
+
+    /// Get funds which could be released to the payee
+    #[ink(message)]
+    fn releasable(&self, account: AccountId) -> Balance {
+        if self.data::<Data>().total_shares == 0 {
+            return 0
+        }
+        let available = Self::env()
+            .balance()
+            .checked_sub(Self::env().minimum_balance())
+            .unwrap_or_default()
+            .saturating_sub(self._reserved_balance());
+        let total_received = available.saturating_add(self.data::<Data>().total_released);
+
+        self._pro_rata_payment(account, total_received)
+            .saturating_sub(self.released(account))
+    }
+
+    /// Get PSP22 `token` funds released to all payees
+    #[ink(message)]
+    fn psp22_total_released(&self, token: AccountId) -> Balance {
+        self.data::<Data>()
+            .psp22_total_released
+            .get(&token)
+            .unwrap_or_default()
+    }
+
+    /// Get PSP22 `token` funds released to the payee
+    #[ink(message)]
+    fn psp22_released(&self, token: AccountId, account: AccountId) -> Balance {
+        self.data::<Data>()
+            .psp22_released
+            .get(&(token, account))
+            .unwrap_or_default()
+    }
+
+    /// Get PSP22 `token` funds which could be released to the payee
+    #[ink(message)]
+    fn psp22_releasable(&self, token: AccountId, account: AccountId) -> Balance {
+        if self.data::<Data>().total_shares == 0 {
+            return 0
+        }
+        let total_received = PSP22Ref::balance_of(&token, Self::env().account_id())
+            .saturating_add(self.psp22_total_released(token));
+
+        self._pro_rata_payment(account, total_received)
+            .saturating_sub(self.psp22_released(token, account))
+    }
+}
+
+/// Helper trait for PaymentSplitter
+pub trait Internal: Storage<Data> {
+    /// Add a payee with its share weight unless payees are locked
+    fn _add_payee(&mut self, account: AccountId, shares: u128) -> Result<(), PSP34Error> {
+        if self.data::<Data>().locked {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::PaymentSplitterLocked.as_str(),
+            ))
+        }
+        let total_shares = self
+            .data::<Data>()
+            .total_shares
+            .checked_add(shares)
+            .filter(|total| shares > 0 && *total <= MAX_TOTAL_SHARES)
+            .ok_or(PSP34Error::Custom(Shiden34Error::InvalidShares.as_str()))?;
+        if self.data::<Data>().shares.get(&account).is_some() {
+            return Err(PSP34Error::Custom(
+                Shiden34Error::PayeeAlreadyExists.as_str(),
+            ))
+        }
+
+        self.data::<Data>().shares.insert(&account, &shares);
+        self.data::<Data>().payees.push(account);
+        self.data::<Data>().total_shares = total_shares;
+
+        Ok(())
+    }
+
+    /// Get the payee part of all funds received, according to its shares
+    fn _pro_rata_payment(&self, account: AccountId, total_received: Balance) -> Balance {
+        let total_shares = self.data::<Data>().total_shares;
+        let shares = self.data::<Data>().shares.get(&account).unwrap_or_default();
+        // split the product so neither term overflows while total_shares is bounded
+        (total_received / total_shares) * shares
+            + (total_received % total_shares) * shares / total_shares
+    }
+
+    /// Get contract balance which is owed elsewhere and must not be split between payees
+    fn _reserved_balance(&self) -> Balance {
+        0
+    }
+}
@@ -0,0 +1,25 @@
+use ink::prelude::vec::Vec;
+use openbrush::{
+    storage::Mapping,
+    traits::{
+        AccountId,
+        Balance,
+    },
+};
+
+/// Upper bound on the sum of all payee shares, keeps pro-rata computations free of overflow
+pub const MAX_TOTAL_SHARES: u128 = u32::MAX as u128;
+
+#[derive(Default, Debug)]
+#[openbrush::storage_item]
+pub struct Data {
+    pub payees: Vec<AccountId>,
+    pub total_shares: u128,
+    pub total_released: Balance,
+    pub shares: Mapping<AccountId, u128>,
+    pub released: Mapping<AccountId, Balance>,
+    /// Set once any funds are released, native or PSP22
+    pub locked: bool,
+    pub psp22_total_released: Mapping<AccountId, Balance>,
+    pub psp22_released: Mapping<(AccountId, AccountId), Balance>,
+}
@@ -0,0 +1,2 @@
+pub mod royalty;
+pub mod types;
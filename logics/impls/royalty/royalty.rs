@@ -0,0 +1,117 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use crate::impls::{
+    payable_mint::types::Shiden34Error,
+    royalty::types::{
+        Data,
+        ROYALTY_DENOMINATOR,
+    },
+};
+use openbrush::{
+    contracts::{
+        ownable,
+        ownable::only_owner,
+        psp34::{
+            Id,
+            PSP34Error,
+        },
+    },
+    modifiers,
+    traits::{
+        AccountId,
+        Balance,
+        Storage,
+    },
+};
+
+#[openbrush::trait_definition]
+pub trait RoyaltyImpl: Storage<Data> + Storage<ownable::Data> {
+    /// Get royalty receiver and amount due for the token sold at `sale_price`
+    #[ink(message)]
+    fn royalty_info(&self, token_id: Id, sale_price: Balance) -> (AccountId, Balance) {
+        let (receiver, bps) = self
+            .data::<Data>()
+            .token_royalties
+            .get(&token_id)
+            .unwrap_or((
+                self.data::<Data>().default_receiver,
+                self.data::<Data>().default_bps,
+            ));
+        let amount = sale_price.saturating_mul(bps as Balance) / ROYALTY_DENOMINATOR as Balance;
+
+        (receiver, amount)
+    }
+
+    /// Get collection-wide royalty receiver and basis points
+    #[ink(message)]
+    fn default_royalty(&self) -> (AccountId, u16) {
+        (
+            self.data::<Data>().default_receiver,
+            self.data::<Data>().default_bps,
+        )
+    }
+
+    /// Set collection-wide royalty receiver and basis points
+    #[ink(message)]
+    #[modifiers(only_owner)]
+    fn set_default_royalty(&mut self, receiver: AccountId, bps: u16) -> Result<(), PSP34Error> {
+        check_royalty_bps(bps)?;
+        self.data::<Data>().default_receiver = receiver;
+        self.data::<Data>().default_bps = bps;
+
+        Ok(())
+    }
+
+    /// Set royalty receiver and basis points for a single token, overriding the default
+    #[ink(message)]
+    #[modifiers(only_owner)]
+    fn set_token_royalty(
+        &mut self,
+        token_id: Id,
+        receiver: AccountId,
+        bps: u16,
+    ) -> Result<(), PSP34Error> {
+        check_royalty_bps(bps)?;
+        self.data::<Data>()
+            .token_royalties
+            .insert(&token_id, &(receiver, bps));
+
+        Ok(())
+    }
+
+    /// Remove royalty override of the token
+    #[ink(message)]
+    #[modifiers(only_owner)]
+    fn reset_token_royalty(&mut self, token_id: Id) -> Result<(), PSP34Error> {
+        self.data::<Data>().token_royalties.remove(&token_id);
+
+        Ok(())
+    }
+}
+
+/// Check if royalty basis points don't exceed the sale price
+fn check_royalty_bps(bps: u16) -> Result<(), PSP34Error> {
+    if bps > ROYALTY_DENOMINATOR {
+        return Err(PSP34Error::Custom(Shiden34Error::InvalidRoyalty.as_str()))
+    }
+    Ok(())
+}
@@ -0,0 +1,16 @@
+use openbrush::{
+    contracts::psp34::Id,
+    storage::Mapping,
+    traits::AccountId,
+};
+
+/// Denominator of royalty basis points
+pub const ROYALTY_DENOMINATOR: u16 = 10_000;
+
+#[derive(Default, Debug)]
+#[openbrush::storage_item]
+pub struct Data {
+    pub default_receiver: AccountId,
+    pub default_bps: u16,
+    pub token_royalties: Mapping<Id, (AccountId, u16)>,
+}
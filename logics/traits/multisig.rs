@@ -0,0 +1,62 @@
+use crate::impls::multisig::types::{
+    AdminAction,
+    Proposal,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::psp34::PSP34Error,
+    traits::{
+        AccountId,
+        Timestamp,
+    },
+};
+
+#[openbrush::wrapper]
+pub type MultisigRef = dyn Multisig;
+
+#[openbrush::trait_definition]
+pub trait Multisig {
+    /// Hand admin actions over to `signers`, of which `threshold` must approve each action.
+    /// Once configured, signers can only be changed through an approved action.
+    /// Operations queued by the timelock before are cancelled
+    #[ink(message)]
+    fn configure_multisig(
+        &mut self,
+        signers: Vec<AccountId>,
+        threshold: u32,
+        proposal_lifetime: Timestamp,
+    ) -> Result<(), PSP34Error>;
+
+    /// Propose admin action, approved by the proposer. Returns proposal id
+    #[ink(message)]
+    fn propose_action(&mut self, action: AdminAction) -> Result<u32, PSP34Error>;
+
+    /// Approve pending admin action
+    #[ink(message)]
+    fn approve_action(&mut self, proposal_id: u32) -> Result<(), PSP34Error>;
+
+    /// Execute admin action approved by enough signers. Withdrawals, new payees, reveal, baseUri
+    /// and max mint amount changes are queued while the timelock delay is set
+    #[ink(message)]
+    fn execute_action(&mut self, proposal_id: u32) -> Result<(), PSP34Error>;
+
+    /// Get accounts approving admin actions
+    #[ink(message)]
+    fn multisig_signers(&self) -> Vec<AccountId>;
+
+    /// Get number of approvals required to execute admin action
+    #[ink(message)]
+    fn multisig_threshold(&self) -> u32;
+
+    /// Get proposed admin action
+    #[ink(message)]
+    fn proposal(&self, proposal_id: u32) -> Option<Proposal>;
+
+    /// Get number of current signers who approved the admin action
+    #[ink(message)]
+    fn approval_count(&self, proposal_id: u32) -> u32;
+
+    /// Check if the signer approved the admin action
+    #[ink(message)]
+    fn has_approved(&self, proposal_id: u32, signer: AccountId) -> bool;
+}
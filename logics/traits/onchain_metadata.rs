@@ -0,0 +1,35 @@
+use ink::prelude::{
+    string::String,
+    vec::Vec,
+};
+
+use openbrush::contracts::psp34::PSP34Error;
+
+#[openbrush::wrapper]
+pub type OnchainMetadataRef = dyn OnchainMetadata;
+
+#[openbrush::trait_definition]
+pub trait OnchainMetadata {
+    /// Store name, description and image of the token on-chain.
+    /// `image` could be an URI or inline image data, e.g. `data:image/svg+xml;base64,...`
+    #[ink(message)]
+    fn set_token_metadata(
+        &mut self,
+        token_id: u64,
+        name: String,
+        description: String,
+        image: String,
+    ) -> Result<(), PSP34Error>;
+
+    /// Store trait attributes of the token on-chain as `(trait_type, value)` pairs
+    #[ink(message)]
+    fn set_token_attributes(
+        &mut self,
+        token_id: u64,
+        attributes: Vec<(String, String)>,
+    ) -> Result<(), PSP34Error>;
+
+    /// Get token URI with JSON metadata rendered from on-chain attributes
+    #[ink(message)]
+    fn onchain_token_uri(&self, token_id: u64) -> Result<String, PSP34Error>;
+}
@@ -1,10 +1,20 @@
-use ink::prelude::string::String;
+use ink::prelude::{
+    string::String,
+    vec::Vec,
+};
 
+use crate::impls::payable_mint::types::{
+    DutchAuction,
+    IdAssignment,
+    SalePhase,
+    TimelockedOperation,
+};
 use openbrush::{
     contracts::psp34::PSP34Error,
     traits::{
         AccountId,
         Balance,
+        Timestamp,
     },
 };
 
@@ -21,15 +31,220 @@ pub trait PayableMint {
     #[ink(message, payable)]
     fn mint_next(&mut self) -> Result<(), PSP34Error>;
 
-    /// Set new value for the baseUri
+    /// Mint one or more tokens and credit the referrer with a share of the native mint price
+    #[ink(message, payable)]
+    fn mint_with_referrer(
+        &mut self,
+        to: AccountId,
+        mint_amount: u64,
+        referrer: AccountId,
+    ) -> Result<(), PSP34Error>;
+
+    /// Mint one or more tokens for the caller from its allowlist allocation
+    #[ink(message, payable)]
+    fn allowlist_mint(
+        &mut self,
+        mint_amount: u64,
+        allocation: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<(), PSP34Error>;
+
+    /// Mint tokens with a voucher signed off-chain by the voucher signer
+    #[ink(message, payable)]
+    fn mint_with_voucher(
+        &mut self,
+        to: AccountId,
+        mint_amount: u64,
+        price_per_mint: Balance,
+        nonce: u64,
+        expiry: Timestamp,
+        signature: [u8; 65],
+    ) -> Result<(), PSP34Error>;
+
+    /// Transfer referral rewards of the caller
+    #[ink(message)]
+    fn claim_referral_rewards(&mut self) -> Result<(), PSP34Error>;
+
+    /// Set share of the mint price credited to referrers, in basis points
+    #[ink(message)]
+    fn set_referral_bps(&mut self, referral_bps: u16) -> Result<(), PSP34Error>;
+
+    /// Mint tokens free of charge to each recipient
+    #[ink(message)]
+    fn airdrop(&mut self, recipients: Vec<(AccountId, u64)>) -> Result<(), PSP34Error>;
+
+    /// Set Merkle root of the allowlist. While set, only allowlist minting is open
+    #[ink(message)]
+    fn set_allowlist_root(&mut self, root: Option<[u8; 32]>) -> Result<(), PSP34Error>;
+
+    /// Add a new sale phase after the existing ones
+    #[ink(message)]
+    fn add_sale_phase(&mut self, phase: SalePhase) -> Result<(), PSP34Error>;
+
+    /// Replace an existing sale phase. Tokens already minted in it are kept
+    #[ink(message)]
+    fn update_sale_phase(&mut self, phase_id: u32, phase: SalePhase) -> Result<(), PSP34Error>;
+
+    /// Set max number of tokens an account could mint over its lifetime. `None` removes the limit
+    #[ink(message)]
+    fn set_max_per_wallet(&mut self, max_per_wallet: Option<u64>) -> Result<(), PSP34Error>;
+
+    /// Set whether tokens minted to another account also count against the recipient's limit
+    #[ink(message)]
+    fn set_wallet_limit_counts_recipient(
+        &mut self,
+        counts_recipient: bool,
+    ) -> Result<(), PSP34Error>;
+
+    /// Set Dutch auction pricing. `None` returns to fixed price. Locked after the first auction mint
+    #[ink(message)]
+    fn set_dutch_auction(&mut self, auction: Option<DutchAuction>) -> Result<(), PSP34Error>;
+
+    /// Claim the difference between the price paid in the Dutch auction and its clearing price
+    #[ink(message)]
+    fn claim_auction_rebate(&mut self) -> Result<(), PSP34Error>;
+
+    /// Reveal the seed which shuffles metadata of tokens with random ids, once the collection
+    /// is sold out or all sale phases are over. It must match the commitment given at
+    /// construction, and no tokens could be minted afterwards
+    #[ink(message)]
+    fn reveal_random_seed(&mut self, seed: [u8; 32]) -> Result<(), PSP34Error>;
+
+    /// Set compressed secp256k1 public key which signs mint vouchers. `None` disables vouchers
+    #[ink(message)]
+    fn set_voucher_signer(&mut self, signer: Option<[u8; 33]>) -> Result<(), PSP34Error>;
+
+    /// Pause minting
+    #[ink(message)]
+    fn pause(&mut self) -> Result<(), PSP34Error>;
+
+    /// Unpause minting
+    #[ink(message)]
+    fn unpause(&mut self) -> Result<(), PSP34Error>;
+
+    /// Set new value for the baseUri. Queued while the timelock delay is set
     #[ink(message)]
     fn set_base_uri(&mut self, uri: String) -> Result<(), PSP34Error>;
 
-    /// Withdraws funds to contract owner
+    /// Set URI returned for the token instead of the one built from baseUri.
+    /// `None` removes the override
+    #[ink(message)]
+    fn set_token_uri(&mut self, token_id: u64, uri: Option<String>) -> Result<(), PSP34Error>;
+
+    /// Permanently lock URI of the token
+    #[ink(message)]
+    fn lock_token_uri(&mut self, token_id: u64) -> Result<(), PSP34Error>;
+
+    /// Permanently freeze baseUri and token metadata. The collection must be revealed first
+    /// if it has a placeholder, and have its starting index set if it has a provenance hash
+    #[ink(message)]
+    fn freeze_metadata(&mut self) -> Result<(), PSP34Error>;
+
+    /// Set URI returned for every token until the collection is revealed
+    #[ink(message)]
+    fn set_placeholder_uri(&mut self, uri: String) -> Result<(), PSP34Error>;
+
+    /// Reveal the collection by setting the real baseUri. This can only be done once.
+    /// Queued while the timelock delay is set
+    #[ink(message)]
+    fn reveal(&mut self, base_uri: String) -> Result<(), PSP34Error>;
+
+    /// Set provenance hash of the collection art, along with the Keccak-256 hash of the seed
+    /// which derives the starting index. Frozen once the first token is minted
+    #[ink(message)]
+    fn set_provenance_hash(
+        &mut self,
+        provenance_hash: [u8; 32],
+        seed_commitment: [u8; 32],
+    ) -> Result<(), PSP34Error>;
+
+    /// Derive the starting index from the seed committed with the provenance hash, once the
+    /// collection is sold out or all sale phases are over. It can be set only once, and not
+    /// after metadata is frozen
+    #[ink(message)]
+    fn set_starting_index(&mut self, seed: [u8; 32]) -> Result<(), PSP34Error>;
+
+    /// Withdraws funds to treasury, or to contract owner if no treasury is set.
+    /// Disabled once payees share the funds. Queued while the timelock delay is set,
+    /// in which case the recipient is fixed when queued
 
     fn withdraw(&mut self) -> Result<(), PSP34Error>;
 
-    /// Set max number of tokens which could be minted per call
+    /// Withdraws `amount` of funds to `recipient`. Disabled once payees share the funds.
+    /// Queued while the timelock delay is set
+    #[ink(message)]
+    fn withdraw_to(&mut self, recipient: AccountId, amount: Balance) -> Result<(), PSP34Error>;
+
+    /// Set account receiving withdrawn funds. `None` withdraws to contract owner
+    #[ink(message)]
+    fn set_treasury(&mut self, treasury: Option<AccountId>) -> Result<(), PSP34Error>;
+
+    /// Propose new contract owner. Ownership is handed over once they accept it
+    #[ink(message)]
+    fn propose_owner(&mut self, new_owner: AccountId) -> Result<(), PSP34Error>;
+
+    /// Accept ownership proposed to the caller, together with the admin and operational roles
+    /// held by the previous owner
+    #[ink(message)]
+    fn accept_ownership(&mut self) -> Result<(), PSP34Error>;
+
+    /// Cancel pending ownership transfer
+    #[ink(message)]
+    fn cancel_ownership_transfer(&mut self) -> Result<(), PSP34Error>;
+
+    /// Set delay for which withdrawals, new payees, reveal, baseUri and max mint amount changes
+    /// are queued before they could be executed. The delay can't be shortened,
+    /// nor exceed `MAX_TIMELOCK_DELAY`
+    #[ink(message)]
+    fn set_timelock_delay(&mut self, delay: Timestamp) -> Result<(), PSP34Error>;
+
+    /// Execute queued operation once its delay has passed
+    #[ink(message)]
+    fn execute_operation(&mut self, operation_id: u32) -> Result<(), PSP34Error>;
+
+    /// Cancel queued operation
+    #[ink(message)]
+    fn cancel_operation(&mut self, operation_id: u32) -> Result<(), PSP34Error>;
+
+    /// Set whether value transferred above the mint price is accepted and refunded,
+    /// instead of failing with `BadMintValue`
+    #[ink(message)]
+    fn set_refund_excess(&mut self, refund_excess: bool) -> Result<(), PSP34Error>;
+
+    /// Set PSP22 token accepted as mint payment and its price per token. `None` disables it
+    #[ink(message)]
+    fn set_psp22_payment(
+        &mut self,
+        token: Option<AccountId>,
+        price_per_mint: Balance,
+    ) -> Result<(), PSP34Error>;
+
+    /// Withdraws PSP22 token balance to treasury, or to contract owner if no treasury is set.
+    /// Disabled once payees share the funds, they are paid with `release_psp22` instead.
+    /// Queued while the timelock delay is set, in which case the recipient is fixed when queued
+    #[ink(message)]
+    fn withdraw_psp22(&mut self, token: AccountId) -> Result<(), PSP34Error>;
+
+    /// Set price per token
+    #[ink(message)]
+    fn set_price(&mut self, price_per_mint: Balance) -> Result<(), PSP34Error>;
+
+    /// Permanently lock price per token
+    #[ink(message)]
+    fn lock_price(&mut self) -> Result<(), PSP34Error>;
+
+    /// Lower max supply of tokens. It can't go below the number of minted tokens,
+    /// nor be changed when tokens get random ids
+    #[ink(message)]
+    fn set_max_supply(&mut self, max_supply: u64) -> Result<(), PSP34Error>;
+
+    /// Permanently close the collection at the number of minted tokens. Not available
+    /// when tokens get random ids
+    #[ink(message)]
+    fn close_collection(&mut self) -> Result<(), PSP34Error>;
+
+    /// Set max number of tokens which could be minted per call.
+    /// Queued while the timelock delay is set
     #[ink(message)]
     fn set_max_mint_amount(&mut self, max_amount: u64) -> Result<(), PSP34Error>;
 
@@ -37,15 +252,135 @@ pub trait PayableMint {
     #[ink(message)]
     fn token_uri(&self, token_id: u64) -> Result<String, PSP34Error>;
 
+    /// Get URI returned for every token until the collection is revealed
+    #[ink(message)]
+    fn placeholder_uri(&self) -> Option<String>;
+
+    /// Check if the collection is revealed
+    #[ink(message)]
+    fn is_revealed(&self) -> bool;
+
+    /// Check if URI of the token is permanently locked
+    #[ink(message)]
+    fn is_token_uri_locked(&self, token_id: u64) -> bool;
+
+    /// Check if metadata is permanently frozen
+    #[ink(message)]
+    fn is_metadata_frozen(&self) -> bool;
+
+    /// Get provenance hash of the collection art
+    #[ink(message)]
+    fn provenance_hash(&self) -> Option<[u8; 32]>;
+
+    /// Get offset applied to token ids in token URIs
+    #[ink(message)]
+    fn starting_index(&self) -> Option<u64>;
+
     /// Get max supply of tokens
     #[ink(message)]
     fn max_supply(&self) -> u64;
 
+    /// Check if the collection is permanently closed
+    #[ink(message)]
+    fn is_collection_closed(&self) -> bool;
+
     /// Get token price
     #[ink(message)]
     fn price(&self) -> Balance;
 
+    /// Check if price per token is locked
+    #[ink(message)]
+    fn is_price_locked(&self) -> bool;
+
     /// Get max number of tokens which could be minted per call
     #[ink(message)]
     fn get_max_mint_amount(&mut self) -> u64;
+
+    /// Get Merkle root of the allowlist
+    #[ink(message)]
+    fn allowlist_root(&self) -> Option<[u8; 32]>;
+
+    /// Get number of tokens minted by the account from its allowlist allocation
+    #[ink(message)]
+    fn allowlist_minted(&self, account: AccountId) -> u64;
+
+    /// Check if minting is paused
+    #[ink(message)]
+    fn paused(&self) -> bool;
+
+    /// Get compressed secp256k1 public key which signs mint vouchers
+    #[ink(message)]
+    fn voucher_signer(&self) -> Option<[u8; 33]>;
+
+    /// Check if the voucher nonce is already used
+    #[ink(message)]
+    fn is_voucher_used(&self, nonce: u64) -> bool;
+
+    /// Get how ids are assigned to newly minted tokens
+    #[ink(message)]
+    fn id_assignment(&self) -> IdAssignment;
+
+    /// Get revealed seed for random token ids
+    #[ink(message)]
+    fn random_seed(&self) -> Option<[u8; 32]>;
+
+    /// Get max number of tokens an account could mint over its lifetime
+    #[ink(message)]
+    fn max_per_wallet(&self) -> Option<u64>;
+
+    /// Get number of tokens counted against the account's lifetime limit
+    #[ink(message)]
+    fn wallet_minted(&self, account: AccountId) -> u64;
+
+    /// Get number of tokens the account could still mint. `None` if there is no limit
+    #[ink(message)]
+    fn wallet_mints_remaining(&self, account: AccountId) -> Option<u64>;
+
+    /// Check if value transferred above the mint price is refunded
+    #[ink(message)]
+    fn refund_excess(&self) -> bool;
+
+    /// Get account receiving withdrawn funds
+    #[ink(message)]
+    fn treasury(&self) -> Option<AccountId>;
+
+    /// Get account proposed as new contract owner
+    #[ink(message)]
+    fn pending_owner(&self) -> Option<AccountId>;
+
+    /// Get delay for which operations are queued
+    #[ink(message)]
+    fn timelock_delay(&self) -> Timestamp;
+
+    /// Get queued operations with their ids
+    #[ink(message)]
+    fn pending_operations(&self) -> Vec<(u32, TimelockedOperation)>;
+
+    /// Get share of the mint price credited to referrers, in basis points
+    #[ink(message)]
+    fn referral_bps(&self) -> u16;
+
+    /// Get referral rewards the account could claim
+    #[ink(message)]
+    fn referral_rewards(&self, account: AccountId) -> Balance;
+
+    /// Get PSP22 token accepted as mint payment with its price per token
+    #[ink(message)]
+    fn psp22_payment(&self) -> Option<(AccountId, Balance)>;
+
+    /// Get Dutch auction pricing, if enabled
+    #[ink(message)]
+    fn dutch_auction(&self) -> Option<DutchAuction>;
+
+    /// Get rebate the account could claim at the current clearing price
+    #[ink(message)]
+    fn auction_rebate(&self, account: AccountId) -> Balance;
+
+    /// Get sale phase by id
+    #[ink(message)]
+    fn sale_phase(&self, phase_id: u32) -> Option<SalePhase>;
+
+    /// Get the active sale phase with its id and remaining supply
+    #[ink(message)]
+    fn current_sale_phase(&self) -> Option<(u32, SalePhase, u64)>;
 }
@@ -0,0 +1,67 @@
+use ink::prelude::vec::Vec;
+
+use openbrush::{
+    contracts::psp34::PSP34Error,
+    traits::{
+        AccountId,
+        Balance,
+    },
+};
+
+#[openbrush::wrapper]
+pub type PaymentSplitterRef = dyn PaymentSplitter;
+
+#[openbrush::trait_definition]
+pub trait PaymentSplitter {
+    /// Add a payee with its share weight. Payees are locked once native or PSP22 funds
+    /// are released. Queued while the timelock delay is set
+    ///
+    /// The sum of all shares can't exceed `MAX_TOTAL_SHARES`
+    #[ink(message)]
+    fn add_payee(&mut self, account: AccountId, shares: u128) -> Result<(), PSP34Error>;
+
+    /// Transfer to the payee its pro-rata part of the received funds, minus what it was already released
+    #[ink(message)]
+    fn release(&mut self, account: AccountId) -> Result<(), PSP34Error>;
+
+    /// Transfer to the payee its pro-rata part of the received PSP22 `token` funds,
+    /// minus what it was already released
+    #[ink(message)]
+    fn release_psp22(&mut self, token: AccountId, account: AccountId) -> Result<(), PSP34Error>;
+
+    /// Get all payees
+    #[ink(message)]
+    fn payees(&self) -> Vec<AccountId>;
+
+    /// Get sum of share weights of all payees
+    #[ink(message)]
+    fn total_shares(&self) -> u128;
+
+    /// Get share weight of the payee
+    #[ink(message)]
+    fn shares(&self, account: AccountId) -> u128;
+
+    /// Get funds released to all payees
+    #[ink(message)]
+    fn total_released(&self) -> Balance;
+
+    /// Get funds released to the payee
+    #[ink(message)]
+    fn released(&self, account: AccountId) -> Balance;
+
+    /// Get funds which could be released to the payee
+    #[ink(message)]
+    fn releasable(&self, account: AccountId) -> Balance;
+
+    /// Get PSP22 `token` funds released to all payees
+    #[ink(message)]
+    fn psp22_total_released(&self, token: AccountId) -> Balance;
+
+    /// Get PSP22 `token` funds released to the payee
+    #[ink(message)]
+    fn psp22_released(&self, token: AccountId, account: AccountId) -> Balance;
+
+    /// Get PSP22 `token` funds which could be released to the payee
+    #[ink(message)]
+    fn psp22_releasable(&self, token: AccountId, account: AccountId) -> Balance;
+}
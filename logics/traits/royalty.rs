@@ -0,0 +1,41 @@
+use openbrush::{
+    contracts::psp34::{
+        Id,
+        PSP34Error,
+    },
+    traits::{
+        AccountId,
+        Balance,
+    },
+};
+
+#[openbrush::wrapper]
+pub type RoyaltyRef = dyn Royalty;
+
+#[openbrush::trait_definition]
+pub trait Royalty {
+    /// Get royalty receiver and amount due for the token sold at `sale_price`
+    #[ink(message)]
+    fn royalty_info(&self, token_id: Id, sale_price: Balance) -> (AccountId, Balance);
+
+    /// Get collection-wide royalty receiver and basis points
+    #[ink(message)]
+    fn default_royalty(&self) -> (AccountId, u16);
+
+    /// Set collection-wide royalty receiver and basis points
+    #[ink(message)]
+    fn set_default_royalty(&mut self, receiver: AccountId, bps: u16) -> Result<(), PSP34Error>;
+
+    /// Set royalty receiver and basis points for a single token, overriding the default
+    #[ink(message)]
+    fn set_token_royalty(
+        &mut self,
+        token_id: Id,
+        receiver: AccountId,
+        bps: u16,
+    ) -> Result<(), PSP34Error>;
+
+    /// Remove royalty override of the token
+    #[ink(message)]
+    fn reset_token_royalty(&mut self, token_id: Id) -> Result<(), PSP34Error>;
+}
@@ -4,6 +4,8 @@ import { encodeAddress } from '@polkadot/keyring';
 import BN from 'bn.js';
 import Shiden_factory from '../typed_contracts/constructors/shiden34';
 import Shiden from '../typed_contracts/contracts/shiden34';
+import Token_factory from '../typed_contracts/constructors/psp22_token';
+import Token from '../typed_contracts/contracts/psp22_token';
 
 import { ApiPromise, WsProvider, Keyring } from '@polkadot/api';
 import type { WeightV2, Weight } from '@polkadot/types/interfaces';
@@ -30,6 +32,8 @@ describe('Minting psp34 tokens', () => {
   let api: ApiPromise;
   let deployer: KeyringPair;
   let bob: KeyringPair;
+  let charlie: KeyringPair;
+  let dave: KeyringPair;
   let contract: Shiden;
 
   const gasLimit = 18750000000;
@@ -42,6 +46,8 @@ describe('Minting psp34 tokens', () => {
     api = await ApiPromise.create({ provider: wsProvider });
     deployer = keyring.addFromUri('//Alice');
     bob = keyring.addFromUri('//Bob');
+    charlie = keyring.addFromUri('//Charlie');
+    dave = keyring.addFromUri('//Dave');
     shidenFactory = new Shiden_factory(api, deployer);
     contract = new Shiden((await shidenFactory.new(
       'Shiden34',
@@ -144,6 +150,46 @@ describe('Minting psp34 tokens', () => {
     expect(mintResult.value?.unwrap().err.custom).to.be.equal('BadMintValue');
   })
 
+  it('PSP22 mint revenue is split between payees', async () => {
+    await setup();
+    const token = new Token((await new Token_factory(api, deployer).new(PRICE_PER_MINT.muln(10))).address, deployer, api);
+
+    // Bob gets tokens and lets the contract pull the price of 4 mints
+    let { gasRequired } = await token.withSigner(deployer).query.transfer(bob.address, PRICE_PER_MINT.muln(4), []);
+    await token.withSigner(deployer).tx.transfer(bob.address, PRICE_PER_MINT.muln(4), [], { gasLimit: getEstimatedGas(gasRequired) });
+    ({ gasRequired } = await token.withSigner(bob).query.approve(contract.address, PRICE_PER_MINT.muln(4)));
+    await token.withSigner(bob).tx.approve(contract.address, PRICE_PER_MINT.muln(4), { gasLimit: getEstimatedGas(gasRequired) });
+
+    ({ gasRequired } = await contract.withSigner(deployer).query.setPsp22Payment(token.address, PRICE_PER_MINT));
+    await contract.withSigner(deployer).tx.setPsp22Payment(token.address, PRICE_PER_MINT, { gasLimit: getEstimatedGas(gasRequired) });
+
+    // mints without transferred value are paid in PSP22
+    for (let i = 0; i < 4; i++) {
+      ({ gasRequired } = await contract.withSigner(bob).query.mintNext());
+      await contract.withSigner(bob).tx.mintNext({ gasLimit: getEstimatedGas(gasRequired) });
+    }
+    expect((await contract.query.totalSupply()).value.unwrap().toNumber()).to.equal(4);
+    expect((await token.query.balanceOf(contract.address)).value.unwrap().toString()).to.equal(PRICE_PER_MINT.muln(4).toString());
+
+    // Charlie and Dave share the revenue 1:3
+    ({ gasRequired } = await contract.withSigner(deployer).query.addPayee(charlie.address, 1));
+    await contract.withSigner(deployer).tx.addPayee(charlie.address, 1, { gasLimit: getEstimatedGas(gasRequired) });
+    ({ gasRequired } = await contract.withSigner(deployer).query.addPayee(dave.address, 3));
+    await contract.withSigner(deployer).tx.addPayee(dave.address, 3, { gasLimit: getEstimatedGas(gasRequired) });
+    expect((await contract.withSigner(deployer).query.withdrawPsp22(token.address)).value?.unwrap().err.custom).to.be.equal('PaymentSplitterActive');
+    expect((await contract.query.psp22Releasable(token.address, charlie.address)).value.unwrap().toString()).to.equal(PRICE_PER_MINT.toString());
+
+    ({ gasRequired } = await contract.withSigner(bob).query.releasePsp22(token.address, charlie.address));
+    await contract.withSigner(bob).tx.releasePsp22(token.address, charlie.address, { gasLimit: getEstimatedGas(gasRequired) });
+    ({ gasRequired } = await contract.withSigner(bob).query.releasePsp22(token.address, dave.address));
+    await contract.withSigner(bob).tx.releasePsp22(token.address, dave.address, { gasLimit: getEstimatedGas(gasRequired) });
+
+    expect((await token.query.balanceOf(charlie.address)).value.unwrap().toString()).to.equal(PRICE_PER_MINT.toString());
+    expect((await token.query.balanceOf(dave.address)).value.unwrap().toString()).to.equal(PRICE_PER_MINT.muln(3).toString());
+    expect((await token.query.balanceOf(contract.address)).value.unwrap().toString()).to.equal('0');
+    expect((await contract.query.psp22TotalReleased(token.address)).value.unwrap().toString()).to.equal(PRICE_PER_MINT.muln(4).toString());
+  })
+
   function getEstimatedGas(gasRequired: Weight): WeightV2 {
     // For some reason Typechain returns wrong type Weigh, although under the hood
     // WeightV2 structure is stored